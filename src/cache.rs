@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use std::io;
+use std::path::Path;
+#[cfg(feature = "cache")]
+use std::{fs, path::PathBuf};
+
+use crate::playerdata::PlayerData;
+
+//the json file parsed games are kept in between runs, named after the netplay code.
+//without the `cache` feature nothing is read or written and every replay is parsed each time
+#[cfg(feature = "cache")]
+pub struct Cache {
+    path: PathBuf,
+    raw: String,
+}
+
+#[cfg(not(feature = "cache"))]
+pub struct Cache;
+
+#[cfg(feature = "cache")]
+impl Cache {
+    pub fn open(dir: &Path, np_code: &str) -> Self {
+        let path = dir.join(format!("{}.cache", np_code));
+        let raw = fs::read_to_string(&path).unwrap_or_default();
+        Self { path, raw }
+    }
+
+    pub fn load(&self) -> Option<PlayerData> {
+        serde_json::from_str(&self.raw).ok()
+    }
+
+    //forgets what was read so every replay gets parsed again
+    pub fn clear(&mut self) {
+        self.raw.clear();
+    }
+
+    pub fn contains(&self, date: &DateTime<Utc>) -> bool {
+        //not sure what's better: this, or loading the deserialized data and then iterating through it and checking each gamedata
+        self.raw.contains(&serde_json::to_string(date).unwrap())
+    }
+
+    pub fn save(&self, data: &PlayerData) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string(data).unwrap())
+    }
+}
+
+#[cfg(not(feature = "cache"))]
+impl Cache {
+    pub fn open(_dir: &Path, _np_code: &str) -> Self {
+        Cache
+    }
+
+    pub fn load(&self) -> Option<PlayerData> {
+        None
+    }
+
+    pub fn clear(&mut self) {}
+
+    pub fn contains(&self, _date: &DateTime<Utc>) -> bool {
+        false
+    }
+
+    pub fn save(&self, _data: &PlayerData) -> io::Result<()> {
+        Ok(())
+    }
+}
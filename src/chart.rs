@@ -0,0 +1,328 @@
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::command::SvgChart;
+use crate::html::escape;
+use crate::player::Player;
+use crate::playerdata::PlayerData;
+use crate::report::{BreakdownReport, DeathsReport, TrendReport};
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 400.0;
+const LEFT: f64 = 50.0;
+const RIGHT: f64 = 20.0;
+const TOP: f64 = 50.0;
+const BOTTOM: f64 = 40.0;
+const BAR_HEIGHT: f64 = 24.0;
+const BAR_LABEL_WIDTH: f64 = 130.0;
+
+const GRID: &str = "#ddd";
+const MAIN: &str = "#2a7";
+const SECOND: &str = "#d55";
+const PALETTE: [&str; 10] = [
+    "#2a7", "#37c", "#e93", "#d55", "#96c", "#4bb", "#c7a", "#8a4", "#b85", "#777",
+];
+
+pub fn export(chart: SvgChart, data: &PlayerData, path: &Path) -> io::Result<()> {
+    fs::write(path, render(chart, data))
+}
+
+pub fn render(chart: SvgChart, data: &PlayerData) -> String {
+    match chart {
+        SvgChart::Trend => trend(&data.trend(Player::Player)),
+        SvgChart::Matchups => bars(&data.matchups(Player::Player)),
+        SvgChart::Stages => pie(&data.stages(Player::Player)),
+        SvgChart::Deaths => histogram(&data.deaths(Player::Player)),
+    }
+}
+
+//line chart of the winrate for each week
+pub fn trend(report: &TrendReport) -> String {
+    let title = format!("Weekly winrate: {}", report.label);
+    if report.points.is_empty() {
+        return empty(&title);
+    }
+    let (x0, x1, y0, y1) = (LEFT, WIDTH - RIGHT, TOP, HEIGHT - BOTTOM);
+    let y = |winrate: f64| y1 - (y1 - y0) * winrate / 100.0;
+    let n = report.points.len();
+    let x = |i: usize| {
+        if n == 1 {
+            (x0 + x1) / 2.0
+        } else {
+            x0 + (x1 - x0) * i as f64 / (n - 1) as f64
+        }
+    };
+    let mut content = percent_axis(y);
+    let points: Vec<String> = report
+        .points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| format!("{:.1},{:.1}", x(i), y(p.record.winrate())))
+        .collect();
+    content.push_str(&format!(
+        "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n",
+        points.join(" "),
+        MAIN
+    ));
+    //only label about eight dates so they don't overlap
+    let label_every = n.div_ceil(8);
+    for (i, point) in report.points.iter().enumerate() {
+        content.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"3\" fill=\"{}\"><title>Week of {}: {}-{} ({:.1}%)</title></circle>\n",
+            x(i),
+            y(point.record.winrate()),
+            MAIN,
+            point.start,
+            point.record.wins,
+            point.record.games - point.record.wins,
+            point.record.winrate()
+        ));
+        if i % label_every == 0 || i == n - 1 {
+            content.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>\n",
+                x(i),
+                y1 + 20.0,
+                point.start.format("%Y-%m-%d")
+            ));
+        }
+    }
+    svg(WIDTH, HEIGHT, &title, &content)
+}
+
+//horizontal bar for each row's winrate, with a line at 50%
+pub fn bars<T: Display>(report: &BreakdownReport<T>) -> String {
+    let title = format!("Winrate: {}", report.label);
+    if report.rows.is_empty() {
+        return empty(&title);
+    }
+    let height = TOP + BAR_HEIGHT * report.rows.len() as f64 + BOTTOM;
+    let (x0, x1) = (BAR_LABEL_WIDTH, WIDTH - RIGHT - 80.0);
+    let x = |winrate: f64| x0 + (x1 - x0) * winrate / 100.0;
+    let mut content = String::new();
+    for (i, row) in report.rows.iter().enumerate() {
+        let top = TOP + BAR_HEIGHT * i as f64;
+        let winrate = row.record.winrate();
+        content.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+            x0 - 8.0,
+            top + BAR_HEIGHT * 0.65,
+            escape(&format!("{}", row.key))
+        ));
+        content.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>\n",
+            x0,
+            top + 3.0,
+            x(winrate) - x0,
+            BAR_HEIGHT - 6.0,
+            winrate_color(winrate)
+        ));
+        content.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\">{}-{} ({:.0}%)</text>\n",
+            x(winrate) + 6.0,
+            top + BAR_HEIGHT * 0.65,
+            row.record.wins,
+            row.record.games - row.record.wins,
+            winrate
+        ));
+    }
+    content.push_str(&format!(
+        "<line x1=\"{0:.1}\" y1=\"{1:.1}\" x2=\"{0:.1}\" y2=\"{2:.1}\" stroke=\"#555\" stroke-dasharray=\"4 3\"/>\n",
+        x(50.0),
+        TOP,
+        height - BOTTOM
+    ));
+    content.push_str(&format!(
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">50%</text>\n",
+        x(50.0),
+        height - BOTTOM + 18.0
+    ));
+    svg(WIDTH, height, &title, &content)
+}
+
+//share of games for each row
+pub fn pie<T: Display>(report: &BreakdownReport<T>) -> String {
+    let title = format!("Games played: {}", report.label);
+    let total: usize = report.rows.iter().map(|r| r.record.games).sum();
+    if total == 0 {
+        return empty(&title);
+    }
+    let (cx, cy, r) = (200.0, (HEIGHT + TOP) / 2.0 - 10.0, 140.0);
+    let point = |fraction: f64| {
+        let angle = fraction * 2.0 * std::f64::consts::PI - std::f64::consts::FRAC_PI_2;
+        (cx + r * angle.cos(), cy + r * angle.sin())
+    };
+    let mut content = String::new();
+    let mut start = 0.0;
+    for (i, row) in report.rows.iter().enumerate() {
+        let fraction = row.record.games as f64 / total as f64;
+        let color = PALETTE[i % PALETTE.len()];
+        let tooltip = format!(
+            "<title>{}: {} games ({:.1}%)</title>",
+            escape(&format!("{}", row.key)),
+            row.record.games,
+            fraction * 100.0
+        );
+        if report.rows.len() == 1 {
+            //an arc can't start and end at the same point
+            content.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\">{}</circle>\n",
+                cx, cy, r, color, tooltip
+            ));
+        } else {
+            let (sx, sy) = point(start);
+            let (ex, ey) = point(start + fraction);
+            content.push_str(&format!(
+                "<path d=\"M {} {} L {:.1} {:.1} A {} {} 0 {} 1 {:.1} {:.1} Z\" fill=\"{}\" stroke=\"white\">{}</path>\n",
+                cx,
+                cy,
+                sx,
+                sy,
+                r,
+                r,
+                if fraction > 0.5 { 1 } else { 0 },
+                ex,
+                ey,
+                color,
+                tooltip
+            ));
+        }
+        start += fraction;
+        let legend_y = TOP + 20.0 * i as f64;
+        content.push_str(&format!(
+            "<rect x=\"400\" y=\"{:.1}\" width=\"12\" height=\"12\" fill=\"{}\"/>\n",
+            legend_y, color
+        ));
+        content.push_str(&format!(
+            "<text x=\"420\" y=\"{:.1}\">{}: {} games ({:.1}%), {:.0}% won</text>\n",
+            legend_y + 11.0,
+            escape(&format!("{}", row.key)),
+            row.record.games,
+            fraction * 100.0,
+            row.record.winrate()
+        ));
+    }
+    let height = HEIGHT.max(TOP + 20.0 * report.rows.len() as f64 + BOTTOM);
+    svg(WIDTH, height, &title, &content)
+}
+
+//stocks lost and taken side by side for each percent bin
+pub fn histogram(report: &DeathsReport) -> String {
+    let title = format!("Percent at stock loss: {}", report.label);
+    let max = report
+        .bins
+        .iter()
+        .map(|b| b.deaths.max(b.kills))
+        .max()
+        .unwrap_or(0);
+    if max == 0 {
+        return empty(&title);
+    }
+    let (x0, x1, y0, y1) = (LEFT, WIDTH - RIGHT, TOP, HEIGHT - BOTTOM);
+    let y = |count: f64| y1 - (y1 - y0) * count / max as f64;
+    let mut content = String::new();
+    for i in 0..=4 {
+        let count = max as f64 * i as f64 / 4.0;
+        content.push_str(&grid_line(x0, x1, y(count), &format!("{:.0}", count)));
+    }
+    let slot = (x1 - x0) / report.bins.len() as f64;
+    let bar = slot * 0.4;
+    for (i, bin) in report.bins.iter().enumerate() {
+        let left = x0 + slot * i as f64 + slot * 0.1;
+        for (j, (count, color, kind)) in [(bin.deaths, SECOND, "lost"), (bin.kills, MAIN, "taken")]
+            .iter()
+            .enumerate()
+        {
+            content.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{}: {} {}</title></rect>\n",
+                left + bar * j as f64,
+                y(*count as f64),
+                bar,
+                y1 - y(*count as f64),
+                color,
+                bin.label(),
+                count,
+                kind
+            ));
+        }
+        content.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>\n",
+            left + bar,
+            y1 + 20.0,
+            bin.label()
+        ));
+    }
+    for (i, (color, label)) in [(SECOND, "Stocks lost"), (MAIN, "Stocks taken")]
+        .iter()
+        .enumerate()
+    {
+        let legend_x = x1 - 220.0 + 110.0 * i as f64;
+        content.push_str(&format!(
+            "<rect x=\"{:.1}\" y=\"30\" width=\"12\" height=\"12\" fill=\"{}\"/>\n<text x=\"{:.1}\" y=\"41\">{}</text>\n",
+            legend_x,
+            color,
+            legend_x + 18.0,
+            label
+        ));
+    }
+    svg(WIDTH, HEIGHT, &title, &content)
+}
+
+//same buckets as the colors in the terminal chart
+pub fn winrate_color(winrate: f64) -> &'static str {
+    if winrate >= 65.0 {
+        "#3b3"
+    } else if winrate >= 55.0 {
+        "#8d8"
+    } else if winrate >= 45.0 {
+        "#dd6"
+    } else if winrate >= 35.0 {
+        "#f99"
+    } else {
+        "#d44"
+    }
+}
+
+fn percent_axis<F: Fn(f64) -> f64>(y: F) -> String {
+    (0..=4)
+        .map(|i| {
+            let percent = 25.0 * i as f64;
+            grid_line(LEFT, WIDTH - RIGHT, y(percent), &format!("{:.0}%", percent))
+        })
+        .collect()
+}
+
+fn grid_line(x0: f64, x1: f64, y: f64, label: &str) -> String {
+    format!(
+        "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\"/>\n<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>\n",
+        x0,
+        y,
+        x1,
+        y,
+        GRID,
+        x0 - 6.0,
+        y + 4.0,
+        label
+    )
+}
+
+fn empty(title: &str) -> String {
+    let content = format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">No data for given input.</text>\n",
+        WIDTH / 2.0,
+        HEIGHT / 2.0
+    );
+    svg(WIDTH, HEIGHT, title, &content)
+}
+
+fn svg(width: f64, height: f64, title: &str, content: &str) -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\" font-size=\"12\">\n<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n<text x=\"{2}\" y=\"24\" font-size=\"16\" text-anchor=\"middle\">{3}</text>\n{4}</svg>\n",
+        width,
+        height,
+        width / 2.0,
+        escape(title),
+        content
+    )
+}
@@ -0,0 +1,407 @@
+use chrono::Utc;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+use crate::command::{self, CommandError};
+use crate::config::{Config, ConfigCommand, ConfigError, Profile, ProfileCommand};
+use crate::filter::{self, Filter, FilterParseError};
+use crate::input;
+use crate::output::{self, OutputFormat, OutputFormatParseError};
+use crate::playerdata::PlayerData;
+use crate::progress::Bar;
+use crate::report::{CompareReport, WinrateReport};
+use crate::script::{self, ScriptError};
+use crate::server;
+use crate::text;
+use crate::tui;
+
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_ERROR: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const DEFAULT_PORT: u16 = 7878;
+
+#[derive(Debug, Default)]
+pub struct Args {
+    pub code: Option<String>,
+    pub dir: Option<PathBuf>,
+    pub filter: Filter,
+    pub format: Option<OutputFormat>,
+    pub port: Option<u16>,
+    pub cache_dir: Option<PathBuf>,
+    pub config: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub help: bool,
+    pub command: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    UnknownFlag(String),
+    MissingValue(String),
+    InvalidCode(String),
+    InvalidDate(FilterParseError),
+    InvalidFormat(OutputFormatParseError),
+    InvalidPort(String),
+    MissingLocation(&'static str),
+    InvalidConfigCommand(String),
+    InvalidProfileCommand(String),
+    InvalidConditions(FilterParseError),
+    Config(ConfigError),
+    Command(CommandError),
+    ReadScript(PathBuf, io::Error),
+    Script(PathBuf, ScriptError),
+    Io(PathBuf, io::Error),
+    Output(io::Error),
+}
+
+//runs the program from command line arguments (without the program name) and returns the exit status
+pub fn run<I: Iterator<Item = String>>(args: I) -> i32 {
+    let args = match parse_args(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("{}", text::USAGE_TEXT);
+            return EXIT_USAGE;
+        }
+    };
+    if args.help {
+        println!("{}", text::USAGE_TEXT);
+        return EXIT_SUCCESS;
+    }
+    let mut config = match Config::load(args.config.clone()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_ERROR;
+        }
+    };
+    if let Some(name) = &args.profile {
+        if let Err(e) = config.select(name) {
+            eprintln!("{}", e);
+            return EXIT_USAGE;
+        }
+    }
+    if let Err(e) = config.active().register_aliases() {
+        eprintln!("{}", e);
+    }
+    let result = match &args.command[..] {
+        [] => interactive(&args, &mut config),
+        [cmd] if cmd.to_lowercase() == "interactive" => interactive(&args, &mut config),
+        [cmd] if cmd.to_lowercase() == "tui" => dashboard(&args, &mut config),
+        [cmd] if cmd.to_lowercase() == "serve" => serve(&args, &config),
+        [cmd, rest @ ..] if cmd.to_lowercase() == "config" => edit_config(rest, &mut config),
+        [cmd, rest @ ..] if cmd.to_lowercase() == "profile" => profiles(rest, &args, &mut config),
+        [cmd, path @ ..] if cmd.to_lowercase() == "run" && !path.is_empty() => {
+            run_script(&args, &config, PathBuf::from(path.join(" ")))
+        }
+        _ => run_command(&args, &config),
+    };
+    match result {
+        Ok(()) => EXIT_SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            match e {
+                CliError::Io(..)
+                | CliError::ReadScript(..)
+                | CliError::Output(_)
+                | CliError::Config(ConfigError::Read(..))
+                | CliError::Config(ConfigError::Parse(..))
+                | CliError::Config(ConfigError::Write(..))
+                | CliError::Config(ConfigError::Serialize(_))
+                | CliError::Config(ConfigError::NoConfigDir) => EXIT_ERROR,
+                _ => EXIT_USAGE,
+            }
+        }
+    }
+}
+
+pub fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Args, CliError> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("--") && arg != "-h" {
+            parsed.command.push(arg);
+            continue;
+        }
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+            None => (arg, None),
+        };
+        if flag == "--help" || flag == "-h" {
+            parsed.help = true;
+            continue;
+        }
+        if ![
+            "--code",
+            "--dir",
+            "--since",
+            "--until",
+            "--format",
+            "--port",
+            "--cache-dir",
+            "--config",
+            "--profile",
+        ]
+        .contains(&&flag[..])
+        {
+            return Err(CliError::UnknownFlag(flag));
+        }
+        let value = match inline_value.or_else(|| args.next()) {
+            Some(value) => value,
+            None => return Err(CliError::MissingValue(flag)),
+        };
+        match &flag[..] {
+            "--code" => match input::parse_code(&value) {
+                Some(code) => parsed.code = Some(code),
+                None => return Err(CliError::InvalidCode(value)),
+            },
+            "--dir" => parsed.dir = Some(PathBuf::from(value)),
+            "--since" => {
+                parsed.filter.since = Some(
+                    filter::parse_date(&value.to_lowercase(), Utc::now())
+                        .map_err(CliError::InvalidDate)?,
+                )
+            }
+            "--until" => {
+                parsed.filter.until = Some(
+                    filter::parse_date(&value.to_lowercase(), Utc::now())
+                        .map_err(CliError::InvalidDate)?,
+                )
+            }
+            "--format" => {
+                parsed.format = Some(
+                    value
+                        .to_lowercase()
+                        .parse()
+                        .map_err(CliError::InvalidFormat)?,
+                )
+            }
+            "--port" => match value.parse() {
+                Ok(port) => parsed.port = Some(port),
+                Err(_) => return Err(CliError::InvalidPort(value)),
+            },
+            "--cache-dir" => parsed.cache_dir = Some(PathBuf::from(value)),
+            "--config" => parsed.config = Some(PathBuf::from(value)),
+            "--profile" => parsed.profile = Some(value),
+            _ => unreachable!(),
+        }
+    }
+    Ok(parsed)
+}
+
+fn run_command(args: &Args, config: &Config) -> Result<(), CliError> {
+    let tokens: Vec<&str> = args.command.iter().map(|t| t.as_str()).collect();
+    let saved = config.expand_query(&tokens);
+    let tokens: Vec<&str> = match &saved {
+        Some(saved) => saved.iter().map(|t| t.as_str()).collect(),
+        None => tokens,
+    };
+    //parse before loading so typos don't have to wait on a directory scan
+    let query = command::parse(&tokens).map_err(CliError::Command)?;
+    let profile = profile(args, config);
+    let mut data = load(&profile)?;
+    data.retain(&args.filter);
+    command::run(&query, &mut data, profile.format.unwrap_or_default()).map_err(CliError::Output)
+}
+
+fn run_script(args: &Args, config: &Config, path: PathBuf) -> Result<(), CliError> {
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => return Err(CliError::ReadScript(path, e)),
+    };
+    let steps = script::parse(&text, config).map_err(|e| CliError::Script(path, e))?;
+    let profile = profile(args, config);
+    let mut data = load(&profile)?;
+    data.retain(&args.filter);
+    script::run(&steps, &mut data, profile.format.unwrap_or_default()).map_err(CliError::Output)
+}
+
+fn interactive(args: &Args, config: &mut Config) -> Result<(), CliError> {
+    let mut data = startup_data(args, config)?;
+    let mut format = profile(args, config).format.unwrap_or_default();
+    loop {
+        data.retain(&args.filter);
+        if input::main_loop(data, &mut format, config) {
+            return Ok(());
+        }
+        data = input::load_data(config);
+    }
+}
+
+fn dashboard(args: &Args, config: &mut Config) -> Result<(), CliError> {
+    let data = startup_data(args, config)?;
+    tui::run(data, args.filter.clone()).map_err(CliError::Output)
+}
+
+//the replays given on the command line, with the config filling in the rest, or else the ones
+//picked at the prompt
+fn startup_data(args: &Args, config: &mut Config) -> Result<PlayerData, CliError> {
+    if args.code.is_none() && args.dir.is_none() && args.cache_dir.is_none() {
+        return Ok(input::load_data(config));
+    }
+    load(&profile(args, config))
+}
+
+fn serve(args: &Args, config: &Config) -> Result<(), CliError> {
+    let profile = profile(args, config);
+    let mut data = load(&profile)?;
+    data.retain(&args.filter);
+    let (code, dir) = location(&profile)?;
+    let location = server::Location {
+        cache_dir: profile.cache_dir(&dir),
+        code,
+        dir,
+        filter: args.filter.clone(),
+        ranking: profile.ranking(),
+    };
+    server::run(data, location, args.port.unwrap_or(DEFAULT_PORT)).map_err(CliError::Output)
+}
+
+fn edit_config(tokens: &[String], config: &mut Config) -> Result<(), CliError> {
+    let words: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+    let command = ConfigCommand::parse(&words)
+        .map_err(|e| usage_error(e, CliError::InvalidConfigCommand(tokens.join(" "))))?;
+    match command {
+        ConfigCommand::Show => {
+            let text = config.show().map_err(CliError::Config)?;
+            println!("# {}", config.path().display());
+            print!("{}", text);
+            Ok(())
+        }
+        ConfigCommand::Path => {
+            println!("{}", config.path().display());
+            Ok(())
+        }
+        command => config.run(&command).map_err(CliError::Config),
+    }
+}
+
+fn profiles(tokens: &[String], args: &Args, config: &mut Config) -> Result<(), CliError> {
+    let words: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+    let command = ProfileCommand::parse(&words)
+        .map_err(|e| usage_error(e, CliError::InvalidProfileCommand(tokens.join(" "))))?;
+    match command {
+        ProfileCommand::List => {
+            print_profiles(config);
+            Ok(())
+        }
+        ProfileCommand::Compare(first, second, filter) => {
+            let format = profile(args, config).format.unwrap_or_default();
+            let report = compare_profiles(config, &first, &second, &filter)?;
+            output::print(&report, format);
+            Ok(())
+        }
+        command => config.run_profile(&command).map_err(CliError::Config),
+    }
+}
+
+//bad codes and conditions get their own message, anything else gets the command's usage
+fn usage_error(e: CommandError, usage: CliError) -> CliError {
+    match e {
+        CommandError::InvalidArgument(code, "netplay code") => CliError::InvalidCode(code),
+        CommandError::InvalidConditions(e) => CliError::InvalidConditions(e),
+        _ => usage,
+    }
+}
+
+//every profile, with the active one marked
+pub fn print_profiles(config: &Config) {
+    for (name, profile) in &config.profiles {
+        let marker = if name == config.active_name() {
+            "*"
+        } else {
+            " "
+        };
+        println!("{} {}: {}", marker, name, profile);
+    }
+}
+
+//the same conditions for two profiles, which usually means two players
+pub fn compare_profiles(
+    config: &Config,
+    first: &str,
+    second: &str,
+    filter: &Filter,
+) -> Result<CompareReport, CliError> {
+    let winrate = |name: &str| -> Result<WinrateReport, CliError> {
+        let profile = config.profile(name).map_err(CliError::Config)?;
+        let mut report = load(profile)?.winrate(filter.clone());
+        report.label = format!("{}: {}", name, report.label);
+        Ok(report)
+    };
+    Ok(CompareReport::new(winrate(first)?, winrate(second)?))
+}
+
+//the active profile from the config, with anything given on the command line replacing it
+fn profile(args: &Args, config: &Config) -> Profile {
+    let mut profile = config.active();
+    if let Some(code) = &args.code {
+        profile.code = Some(code.clone());
+    }
+    if let Some(dir) = &args.dir {
+        profile.dir = Some(dir.clone());
+    }
+    if let Some(cache_dir) = &args.cache_dir {
+        profile.cache_dir = Some(cache_dir.clone());
+    }
+    if let Some(format) = args.format {
+        profile.format = Some(format);
+    }
+    profile
+}
+
+fn location(profile: &Profile) -> Result<(String, PathBuf), CliError> {
+    let code = profile
+        .code
+        .clone()
+        .ok_or(CliError::MissingLocation("--code"))?;
+    let dir = profile
+        .dir
+        .clone()
+        .ok_or(CliError::MissingLocation("--dir"))?;
+    Ok((code, dir))
+}
+
+pub fn load(profile: &Profile) -> Result<PlayerData, CliError> {
+    let (code, dir) = location(profile)?;
+    let cache_dir = profile.cache_dir(&dir);
+    let mut data = PlayerData::parse_dir_with(dir.clone(), code, &cache_dir, &mut Bar::new())
+        .map_err(|e| CliError::Io(dir, e))?;
+    data.set_ranking(profile.ranking());
+    Ok(data)
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::UnknownFlag(flag) => write!(f, "Unrecognized option `{}`.", flag),
+            CliError::MissingValue(flag) => write!(f, "`{}` needs a value after it.", flag),
+            CliError::InvalidCode(code) => write!(f, "`{}` is not a valid netplay code.", code),
+            CliError::InvalidDate(e) => write!(f, "{}", e),
+            CliError::InvalidFormat(e) => write!(f, "{}", e),
+            CliError::InvalidPort(port) => write!(f, "`{}` is not a valid port.", port),
+            CliError::MissingLocation(flag) => write!(
+                f,
+                "No replay location in the config, pass `{}`, set it with `config set` or run in interactive mode once to save one.",
+                flag
+            ),
+            CliError::InvalidConfigCommand(command) => write!(
+                f,
+                "Unrecognized config command `{}`, use show, path, set <KEY> <VALUE>, unset <KEY>, alias character|stage <ALIAS> <NAME>, unalias character|stage <ALIAS>, query <NAME> <COMMAND> or unquery <NAME>.",
+                command
+            ),
+            CliError::InvalidProfileCommand(command) => write!(
+                f,
+                "Unrecognized profile command `{}`, use list, add <NAME> <CODE> <DIR>, remove <NAME>, switch <NAME> or compare <NAME> <NAME> [CONDITIONS].",
+                command
+            ),
+            CliError::InvalidConditions(e) => write!(f, "{}", e),
+            CliError::Config(e) => write!(f, "{}", e),
+            CliError::Command(e) => write!(f, "{}", e),
+            CliError::Io(dir, e) => write!(f, "Couldn't load replays from {:?}: {}", dir, e),
+            CliError::Output(e) => write!(f, "Command failed: {}", e),
+            CliError::ReadScript(path, e) => write!(f, "Couldn't read script {:?}: {}", path, e),
+            CliError::Script(path, e) => write!(f, "{}, {}", path.display(), e),
+        }
+    }
+}
@@ -0,0 +1,744 @@
+use std::fmt;
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::character::Character;
+use crate::chart;
+use crate::config;
+use crate::database;
+use crate::dolphin::{self, Queue};
+use crate::filter::{Filter, FilterParseError};
+use crate::frames;
+use crate::gamedata::GameData;
+use crate::gamelog::GameLog;
+use crate::html;
+use crate::output::{self, OutputFormat};
+use crate::parsable_enum::{self, GameDataCondition, Parsable, ParsableEnumError};
+use crate::player::Player;
+use crate::playerdata::PlayerData;
+use crate::report::GameDetailReport;
+use crate::stage::Stage;
+use crate::stats::Ranking;
+
+//a fully parsed command, shared by the command line arguments and anything else that takes a whole command at once
+#[derive(Debug)]
+pub enum Command {
+    Player(Stat),
+    Character(Character, Stat),
+    Stage(Stage, Stat),
+    Matchup(Character, Character),
+    Chart(Chart),
+    Compare(Filter, Filter),
+    Last(usize),
+    Games(GameLog),
+    Game(GameRef),
+    Rating(RatingCommand),
+    Ranking(Ranking),
+    Sessions,
+    Trend,
+    Export(Export),
+    Sql(String),
+}
+
+//a command and the conditions for which games it counts, like `character fox matchups since:30d`.
+//`format:table` among the conditions prints just this command differently
+#[derive(Debug)]
+pub struct Query {
+    pub command: Command,
+    pub filter: Filter,
+    pub format: Option<OutputFormat>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Stat {
+    Winrate,
+    Characters,
+    Stages,
+    Matchups,
+    Overview,
+    Deaths,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Chart {
+    Matchups,
+    Stages,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SvgChart {
+    Trend,
+    Matchups,
+    Stages,
+    Deaths,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum RatingCommand {
+    Current,
+    History,
+    Character(Character),
+}
+
+//a game by its number in the game log, or any replay file of the player's
+#[derive(Debug, PartialEq)]
+pub enum GameRef {
+    Id(usize),
+    Path(PathBuf),
+}
+
+#[derive(Debug)]
+pub enum Export {
+    Html(PathBuf),
+    Svg(SvgChart, PathBuf),
+    Sqlite(PathBuf),
+    //the game's number among the ones matching the conditions, or all of them
+    Frames(PathBuf, Option<usize>),
+    Dolphin(PathBuf, Queue),
+}
+
+#[derive(Debug)]
+pub enum CommandError {
+    UnknownCommand(String),
+    MissingArgument(&'static str),
+    InvalidArgument(String, &'static str),
+    InvalidName(ParsableEnumError),
+    InvalidConditions(FilterParseError),
+}
+
+//every command `parse` takes, so other things can't take their names
+pub const NAMES: [&str; 15] = [
+    "player",
+    "character",
+    "stage",
+    "matchup",
+    "chart",
+    "compare",
+    "last",
+    "games",
+    "game",
+    "rating",
+    "ranking",
+    "sessions",
+    "trend",
+    "export",
+    "sql",
+];
+
+const GAME_LOG_OPTIONS: [&str; 3] = ["sort:", "page:", "search:"];
+const EXPORT_OPTIONS: [&str; 4] = ["game:", "clips:", "below:", "above:"];
+
+const PLAYER_STATS: &[Stat] = &[
+    Stat::Winrate,
+    Stat::Characters,
+    Stat::Stages,
+    Stat::Matchups,
+    Stat::Overview,
+    Stat::Deaths,
+];
+const CHARACTER_STATS: &[Stat] = &[Stat::Winrate, Stat::Stages, Stat::Matchups, Stat::Deaths];
+const STAGE_STATS: &[Stat] = &[
+    Stat::Winrate,
+    Stat::Characters,
+    Stat::Matchups,
+    Stat::Deaths,
+];
+
+//everything except file paths is case insensitive. conditions can follow the stat of `player`,
+//`character` and `stage`, or start at the first `key:value` word of the other stats commands
+pub fn parse(raw_tokens: &[&str]) -> Result<Query, CommandError> {
+    let tokens: Vec<String> = raw_tokens.iter().map(|t| t.to_lowercase()).collect();
+    let tokens: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+    let (first, rest) = match tokens.split_first() {
+        Some(split) => split,
+        None => return Err(CommandError::MissingArgument("command")),
+    };
+    let (rest, conditions): (Vec<&str>, Vec<&str>) = match *first {
+        "player" | "character" | "stage" => {
+            match rest.iter().position(|t| Stat::from_str(t).is_ok()) {
+                Some(i) => (rest[..=i].to_vec(), rest[i + 1..].to_vec()),
+                None => (rest.to_vec(), Vec::new()),
+            }
+        }
+        "matchup" | "chart" | "last" | "rating" | "sessions" | "trend" => {
+            let i = rest
+                .iter()
+                .position(|t| t.contains(':'))
+                .unwrap_or(rest.len());
+            (rest[..i].to_vec(), rest[i..].to_vec())
+        }
+        //everything is a condition except the log's own options, wherever they are
+        "games" => rest
+            .iter()
+            .copied()
+            .partition(|t| GAME_LOG_OPTIONS.iter().any(|o| t.starts_with(o))),
+        //a number can be followed by conditions, a path is taken whole
+        "game" => match rest.first() {
+            Some(id) if id.parse::<usize>().is_ok() => (rest[..1].to_vec(), rest[1..].to_vec()),
+            _ => (rest.to_vec(), Vec::new()),
+        },
+        //the file path can have spaces, so conditions start at the first `key:value` word after it
+        "export" if rest.first() == Some(&"frames") || rest.first() == Some(&"dolphin") => {
+            let i = rest
+                .iter()
+                .skip(2)
+                .position(|t| is_export_option(t) || is_condition(t))
+                .map_or(rest.len(), |i| i + 2);
+            let (options, conditions): (Vec<&str>, Vec<&str>) =
+                rest[i..].iter().copied().partition(|t| is_export_option(t));
+            ([&rest[..i], &options[..]].concat(), conditions)
+        }
+        _ => (rest.to_vec(), Vec::new()),
+    };
+    let mut format = None;
+    let mut words = Vec::new();
+    for word in &conditions {
+        match word.strip_prefix("format:") {
+            Some(name) => format = Some(parse_arg(name, "output format")?),
+            None => words.push(*word),
+        }
+    }
+    let filter = if words.is_empty() {
+        Filter::new()
+    } else {
+        parse_conditions(&words.join(" "))?
+    };
+    Ok(Query {
+        command: parse_command(first, &rest, raw_tokens)?,
+        filter,
+        format,
+    })
+}
+
+fn parse_command(first: &str, rest: &[&str], raw_tokens: &[&str]) -> Result<Command, CommandError> {
+    match first {
+        "player" => Ok(Command::Player(parse_stat(rest, PLAYER_STATS)?)),
+        "character" => {
+            let (character, stat) = split_stat(rest, CHARACTER_STATS, "character")?;
+            Ok(Command::Character(character, stat))
+        }
+        "stage" => {
+            let (stage, stat) = split_stat(rest, STAGE_STATS, "stage")?;
+            Ok(Command::Stage(stage, stat))
+        }
+        "matchup" => {
+            let (player, opponent) = parse_matchup(rest)?;
+            Ok(Command::Matchup(player, opponent))
+        }
+        "chart" => match rest {
+            ["matchups"] => Ok(Command::Chart(Chart::Matchups)),
+            ["stages"] => Ok(Command::Chart(Chart::Stages)),
+            [] => Err(CommandError::MissingArgument("chart type")),
+            _ => Err(CommandError::InvalidArgument(rest.join(" "), "chart type")),
+        },
+        "compare" if rest.is_empty() => {
+            Err(CommandError::MissingArgument("first set of conditions"))
+        }
+        "compare" => {
+            let joined = rest.join(" ");
+            let (first, second) = match joined.split_once('/') {
+                Some(split) => split,
+                None => return Err(CommandError::MissingArgument("second set of conditions")),
+            };
+            Ok(Command::Compare(
+                parse_conditions(first.trim())?,
+                parse_conditions(second.trim())?,
+            ))
+        }
+        "last" => Ok(Command::Last(parse_arg(
+            &rest.join(" "),
+            "number of games",
+        )?)),
+        "games" => {
+            let mut log = GameLog::new();
+            for word in rest {
+                match word.split_once(':') {
+                    Some(("sort", sort)) => log.sort = parse_arg(sort, "sort order")?,
+                    Some(("page", page)) => log.page = parse_arg(page, "page")?,
+                    Some(("search", text)) if !text.is_empty() => {
+                        log.search = Some(text.to_string())
+                    }
+                    _ => {
+                        return Err(CommandError::InvalidArgument(
+                            word.to_string(),
+                            "game log option",
+                        ))
+                    }
+                }
+            }
+            Ok(Command::Games(log))
+        }
+        "game" => match rest {
+            [] => Err(CommandError::MissingArgument("game number or replay path")),
+            [id] if id.parse::<usize>().is_ok() => {
+                Ok(Command::Game(GameRef::Id(parse_arg(id, "game number")?)))
+            }
+            _ => Ok(Command::Game(GameRef::Path(PathBuf::from(
+                raw_tokens[1..].join(" "),
+            )))),
+        },
+        "rating" => match rest {
+            ["current"] => Ok(Command::Rating(RatingCommand::Current)),
+            ["history"] => Ok(Command::Rating(RatingCommand::History)),
+            ["character"] => Err(CommandError::MissingArgument("character")),
+            ["character", name @ ..] => Ok(Command::Rating(RatingCommand::Character(parse_name(
+                &name.join(" "),
+                "character",
+            )?))),
+            [] => Err(CommandError::MissingArgument("rating command")),
+            _ => Err(CommandError::InvalidArgument(
+                rest.join(" "),
+                "rating command",
+            )),
+        },
+        "ranking" => match rest {
+            ["lower", "bound"] => Ok(Command::Ranking(Ranking::LowerBound)),
+            ["shrunk", games, winrate] => {
+                let prior_games: f64 = parse_arg(games, "number of prior games")?;
+                let prior_winrate: f64 = parse_arg(winrate, "prior winrate")?;
+                Ok(Command::Ranking(Ranking::Shrunk {
+                    prior_games: prior_games.max(0.0),
+                    prior_winrate: prior_winrate.clamp(0.0, 100.0) / 100.0,
+                }))
+            }
+            ["shrunk", ..] => Err(CommandError::MissingArgument("prior games and winrate")),
+            [] => Err(CommandError::MissingArgument("ranking method")),
+            _ => Err(CommandError::InvalidArgument(
+                rest.join(" "),
+                "ranking method",
+            )),
+        },
+        "sessions" => Ok(Command::Sessions),
+        "trend" => Ok(Command::Trend),
+        "export" => match rest {
+            ["html"] => Err(CommandError::MissingArgument("file path")),
+            ["html", ..] => Ok(Command::Export(Export::Html(PathBuf::from(
+                raw_tokens[2..].join(" "),
+            )))),
+            ["svg"] => Err(CommandError::MissingArgument("chart")),
+            ["svg", _] => Err(CommandError::MissingArgument("file path")),
+            ["svg", chart, ..] => Ok(Command::Export(Export::Svg(
+                parse_arg(chart, "chart")?,
+                PathBuf::from(raw_tokens[3..].join(" ")),
+            ))),
+            ["sqlite"] => Err(CommandError::MissingArgument("file path")),
+            ["sqlite", ..] => Ok(Command::Export(Export::Sqlite(PathBuf::from(
+                raw_tokens[2..].join(" "),
+            )))),
+            ["frames", args @ ..] => {
+                let (path, options) = split_export_path(args, raw_tokens)?;
+                let mut game = None;
+                for option in options {
+                    match option.split_once(':') {
+                        Some(("game", id)) => game = Some(parse_arg(id, "game number")?),
+                        _ => {
+                            return Err(CommandError::InvalidArgument(
+                                option.to_string(),
+                                "frames option",
+                            ))
+                        }
+                    }
+                }
+                Ok(Command::Export(Export::Frames(path, game)))
+            }
+            ["dolphin", args @ ..] => {
+                let (path, options) = split_export_path(args, raw_tokens)?;
+                let mut queue = Queue::default();
+                for option in options {
+                    match option.split_once(':') {
+                        Some(("game", id)) => queue.game = Some(parse_arg(id, "game number")?),
+                        Some(("clips", stocks)) => {
+                            queue.clips = Some(parse_arg(stocks, "clip type")?)
+                        }
+                        Some(("below", percent)) => {
+                            queue.below = Some(parse_arg(percent.trim_end_matches('%'), "percent")?)
+                        }
+                        Some(("above", percent)) => {
+                            queue.above = Some(parse_arg(percent.trim_end_matches('%'), "percent")?)
+                        }
+                        _ => {
+                            return Err(CommandError::InvalidArgument(
+                                option.to_string(),
+                                "playback queue option",
+                            ))
+                        }
+                    }
+                }
+                if queue.clips.is_none() && (queue.below.is_some() || queue.above.is_some()) {
+                    return Err(CommandError::MissingArgument("clip type"));
+                }
+                Ok(Command::Export(Export::Dolphin(path, queue)))
+            }
+            [] => Err(CommandError::MissingArgument("export format")),
+            _ => Err(CommandError::InvalidArgument(
+                rest.join(" "),
+                "export format",
+            )),
+        },
+        //queries keep their case since string comparisons in sql are case sensitive
+        "sql" if rest.is_empty() => Err(CommandError::MissingArgument("query")),
+        "sql" => Ok(Command::Sql(raw_tokens[1..].join(" "))),
+        _ => Err(CommandError::UnknownCommand(raw_tokens.join(" "))),
+    }
+}
+
+//the words that can fill in a missing argument of `command`, for asking for it and completing it
+pub fn choices(command: &str, kind: &str) -> Vec<&'static str> {
+    let stats = |stats: &[Stat]| stats.iter().map(|s| s.name()).collect();
+    match (command, kind) {
+        ("player", "stat") => stats(PLAYER_STATS),
+        ("character", "stat") => stats(CHARACTER_STATS),
+        ("stage", "stat") => stats(STAGE_STATS),
+        ("chart", "chart type") => vec!["matchups", "stages"],
+        ("rating", "rating command") => vec!["current", "history", "character"],
+        ("ranking", "ranking method") => vec!["lower bound", "shrunk"],
+        ("export", "export format") => vec!["html", "svg", "sqlite", "frames", "dolphin"],
+        ("export", "clip type") => vec!["clips:deaths", "clips:kills"],
+        ("export", "chart") => vec!["trend", "matchups", "stages", "deaths"],
+        ("config", "config key") => config::KEYS.to_vec(),
+        ("config", "alias kind") => config::ALIAS_KINDS.to_vec(),
+        _ => Vec::new(),
+    }
+}
+
+pub fn run(query: &Query, data: &mut PlayerData, format: OutputFormat) -> io::Result<()> {
+    let stdout = io::stdout();
+    let color = stdout.is_terminal();
+    run_query(query, data, format, color, &mut stdout.lock())
+}
+
+//prints reports to `out` instead of stdout, without colours. the conditions only apply to this
+//query, not to `data`
+pub fn run_to(
+    query: &Query,
+    data: &mut PlayerData,
+    format: OutputFormat,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    run_query(query, data, format, false, out)
+}
+
+fn run_query(
+    query: &Query,
+    data: &mut PlayerData,
+    format: OutputFormat,
+    color: bool,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    let format = query.format.unwrap_or(format);
+    if query.filter.is_empty() {
+        return run_command(&query.command, data, format, color, out);
+    }
+    let mut filtered = data.clone();
+    filtered.retain(&query.filter);
+    run_command(&query.command, &mut filtered, format, color, out)
+}
+
+fn run_command(
+    command: &Command,
+    data: &mut PlayerData,
+    format: OutputFormat,
+    color: bool,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    match command {
+        Command::Player(stat) => run_stat(data, *stat, Player::Player, format, out)?,
+        Command::Character(character, stat) => run_stat(data, *stat, *character, format, out)?,
+        Command::Stage(stage, stat) => run_stat(data, *stat, *stage, format, out)?,
+        Command::Matchup(player, opponent) => {
+            output::write_colored(out, &data.matchup(*player, *opponent), format, color)?
+        }
+        Command::Chart(Chart::Matchups) => {
+            output::write_colored(out, &data.matchup_chart(), format, color)?
+        }
+        Command::Chart(Chart::Stages) => {
+            output::write_colored(out, &data.stage_chart(), format, color)?
+        }
+        Command::Compare(first, second) => {
+            output::write_colored(out, &data.compare(first, second), format, color)?
+        }
+        Command::Last(num) => output::write_colored(out, &data.last(*num), format, color)?,
+        Command::Games(log) => output::write_colored(out, &data.game_log(log), format, color)?,
+        Command::Game(game) => {
+            output::write_colored(out, &game_detail(game, data)?, format, color)?
+        }
+        Command::Rating(RatingCommand::Current) => {
+            output::write_colored(out, &data.ratings(), format, color)?
+        }
+        Command::Rating(RatingCommand::History) => {
+            output::write_colored(out, &data.rating_history(Player::Player), format, color)?
+        }
+        Command::Rating(RatingCommand::Character(character)) => {
+            output::write_colored(out, &data.rating_history(*character), format, color)?
+        }
+        Command::Ranking(ranking) => data.set_ranking(*ranking),
+        Command::Sessions => output::write_colored(out, &data.sessions(), format, color)?,
+        Command::Trend => output::write_colored(out, &data.trend(Player::Player), format, color)?,
+        Command::Export(Export::Html(path)) => {
+            html::export(data, path)?;
+            writeln!(out, "Report saved to {:?}.", path)?;
+        }
+        Command::Export(Export::Svg(svg, path)) => {
+            chart::export(*svg, data, path)?;
+            writeln!(out, "Chart saved to {:?}.", path)?;
+        }
+        Command::Export(Export::Sqlite(path)) => {
+            database::export(data, path)?;
+            writeln!(out, "Database saved to {:?}.", path)?;
+        }
+        Command::Export(Export::Frames(path, game)) => {
+            let written = frames::export(&export_games(data, *game)?, path)?;
+            writeln!(out, "Frames of {} games saved to {:?}.", written, path)?;
+        }
+        Command::Export(Export::Dolphin(path, queue)) => {
+            let games = export_games(data, queue.game)?;
+            let queued = dolphin::export(&games, queue, data.code(), path)?;
+            let entries = match queue.clips {
+                Some(_) => "clips",
+                None => "games",
+            };
+            writeln!(
+                out,
+                "Playback queue of {} {} saved to {:?}.",
+                queued, entries, path
+            )?;
+        }
+        Command::Sql(sql) => {
+            let report = database::query(data, sql).map_err(io::Error::other)?;
+            output::write_colored(out, &report, format, color)?
+        }
+    }
+    Ok(())
+}
+
+fn no_game(id: usize, data: &PlayerData) -> io::Error {
+    io::Error::other(format!(
+        "There's no game {}, there are {} games.",
+        id,
+        data.games().len()
+    ))
+}
+
+//one game by its number, or all of them
+fn export_games(data: &PlayerData, game: Option<usize>) -> io::Result<Vec<&GameData>> {
+    match game {
+        Some(id) => data
+            .game(id)
+            .map(|g| vec![g])
+            .ok_or_else(|| no_game(id, data)),
+        None => Ok(data.games().iter().collect()),
+    }
+}
+
+fn game_detail(game: &GameRef, data: &PlayerData) -> io::Result<GameDetailReport> {
+    let path = match game {
+        GameRef::Id(id) => match data.game(*id) {
+            Some(game) if game.path.as_os_str().is_empty() => {
+                return Err(io::Error::other(format!("Game {} has no replay file.", id)))
+            }
+            Some(game) => &game.path,
+            None => return Err(no_game(*id, data)),
+        },
+        GameRef::Path(path) => path,
+    };
+    if !path.is_file() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No replay at {:?}.", path),
+        ));
+    }
+    GameData::detail(path, data.code())
+        .map_err(|e| io::Error::other(format!("Couldn't show {:?}: {}.", path, e)))
+}
+
+fn run_stat<T: GameDataCondition + Display>(
+    data: &PlayerData,
+    stat: Stat,
+    arg: T,
+    format: OutputFormat,
+    out: &mut dyn Write,
+) -> io::Result<()> {
+    match stat {
+        Stat::Winrate => output::write(out, &data.winrate(arg), format),
+        Stat::Characters => output::write(out, &data.characters(arg), format),
+        Stat::Stages => output::write(out, &data.stages(arg), format),
+        Stat::Matchups => output::write(out, &data.matchups(arg), format),
+        Stat::Overview => output::write(out, &data.overview(), format),
+        Stat::Deaths => output::write(out, &data.deaths(arg), format),
+    }
+}
+
+fn parse_arg<T: FromStr>(arg: &str, kind: &'static str) -> Result<T, CommandError> {
+    if arg.is_empty() {
+        return Err(CommandError::MissingArgument(kind));
+    }
+    arg.parse()
+        .map_err(|_| CommandError::InvalidArgument(arg.to_string(), kind))
+}
+
+fn parse_name<T: Parsable>(arg: &str, kind: &'static str) -> Result<T, CommandError> {
+    if arg.is_empty() {
+        return Err(CommandError::MissingArgument(kind));
+    }
+    parsable_enum::parse(arg).map_err(CommandError::InvalidName)
+}
+
+fn is_condition(token: &str) -> bool {
+    token
+        .split_once(':')
+        .is_some_and(|(key, _)| Filter::is_keyword(key))
+}
+
+fn is_export_option(token: &str) -> bool {
+    EXPORT_OPTIONS.iter().any(|o| token.starts_with(o))
+}
+
+//the file path of `export frames` and `export dolphin` and the options after it
+fn split_export_path<'a>(
+    args: &[&'a str],
+    raw_tokens: &[&str],
+) -> Result<(PathBuf, Vec<&'a str>), CommandError> {
+    let end = args
+        .iter()
+        .position(|t| is_export_option(t))
+        .unwrap_or(args.len());
+    if end == 0 {
+        return Err(CommandError::MissingArgument("file path"));
+    }
+    Ok((
+        PathBuf::from(raw_tokens[2..2 + end].join(" ")),
+        args[end..].to_vec(),
+    ))
+}
+
+fn parse_conditions(arg: &str) -> Result<Filter, CommandError> {
+    if arg.is_empty() {
+        return Err(CommandError::MissingArgument("set of conditions"));
+    }
+    arg.parse().map_err(CommandError::InvalidConditions)
+}
+
+fn parse_stat(tokens: &[&str], allowed: &[Stat]) -> Result<Stat, CommandError> {
+    match tokens {
+        [] => Err(CommandError::MissingArgument("stat")),
+        [word] => match Stat::from_str(word) {
+            Ok(stat) if allowed.contains(&stat) => Ok(stat),
+            _ => Err(CommandError::InvalidArgument(word.to_string(), "stat")),
+        },
+        _ => Err(CommandError::InvalidArgument(tokens.join(" "), "stat")),
+    }
+}
+
+//for commands like `character captain falcon matchups`, where the name can be several words long
+fn split_stat<T: Parsable>(
+    tokens: &[&str],
+    allowed: &[Stat],
+    kind: &'static str,
+) -> Result<(T, Stat), CommandError> {
+    match tokens.split_last() {
+        None => Err(CommandError::MissingArgument(kind)),
+        Some((last, name)) => match Stat::from_str(last) {
+            Ok(_) if name.is_empty() => Err(CommandError::MissingArgument(kind)),
+            Ok(stat) if allowed.contains(&stat) => Ok((parse_name(&name.join(" "), kind)?, stat)),
+            Ok(_) => Err(CommandError::InvalidArgument(last.to_string(), "stat")),
+            //either the stat is left out or it isn't one, which depends on whether the rest is a name
+            Err(_) => match parse_name::<T>(&tokens.join(" "), kind) {
+                Ok(_) => Err(CommandError::MissingArgument("stat")),
+                Err(e) if name.is_empty() || parse_name::<T>(&name.join(" "), kind).is_err() => {
+                    Err(e)
+                }
+                Err(_) => Err(CommandError::InvalidArgument(last.to_string(), "stat")),
+            },
+        },
+    }
+}
+
+//accepts `fox marth`, `fox vs marth`, and multi word names like `captain falcon ice climbers`
+fn parse_matchup(tokens: &[&str]) -> Result<(Character, Character), CommandError> {
+    if let Some(i) = tokens.iter().position(|t| *t == "vs" || *t == "vs.") {
+        return Ok((
+            parse_name(&tokens[..i].join(" "), "player character")?,
+            parse_name(&tokens[i + 1..].join(" "), "opponent character")?,
+        ));
+    }
+    //full names first, so a prefix of the first name isn't taken as the whole thing
+    for i in 1..tokens.len() {
+        let player = parsable_enum::parse_exact::<Character>(&tokens[..i].join(" "));
+        let opponent = parsable_enum::parse_exact::<Character>(&tokens[i..].join(" "));
+        if let (Some(player), Some(opponent)) = (player, opponent) {
+            return Ok((player, opponent));
+        }
+    }
+    for i in 1..tokens.len() {
+        let player = Character::from_str(&tokens[..i].join(" "));
+        let opponent = Character::from_str(&tokens[i..].join(" "));
+        if let (Ok(player), Ok(opponent)) = (player, opponent) {
+            return Ok((player, opponent));
+        }
+    }
+    match tokens {
+        [] => Err(CommandError::MissingArgument("player character")),
+        [player] => {
+            parse_name::<Character>(player, "player character")?;
+            Err(CommandError::MissingArgument("opponent character"))
+        }
+        _ => Err(CommandError::InvalidArgument(tokens.join(" "), "matchup")),
+    }
+}
+
+impl Stat {
+    fn name(self) -> &'static str {
+        match self {
+            Stat::Winrate => "winrate",
+            Stat::Characters => "characters",
+            Stat::Stages => "stages",
+            Stat::Matchups => "matchups",
+            Stat::Overview => "overview",
+            Stat::Deaths => "deaths",
+        }
+    }
+}
+
+impl FromStr for Stat {
+    type Err = CommandError;
+    fn from_str(arg: &str) -> Result<Self, Self::Err> {
+        match arg {
+            "winrate" => Ok(Stat::Winrate),
+            "characters" => Ok(Stat::Characters),
+            "stages" => Ok(Stat::Stages),
+            "matchups" => Ok(Stat::Matchups),
+            "overview" => Ok(Stat::Overview),
+            "deaths" => Ok(Stat::Deaths),
+            _ => Err(CommandError::InvalidArgument(arg.to_string(), "stat")),
+        }
+    }
+}
+
+impl FromStr for SvgChart {
+    type Err = CommandError;
+    fn from_str(arg: &str) -> Result<Self, Self::Err> {
+        match arg {
+            "trend" => Ok(SvgChart::Trend),
+            "matchups" => Ok(SvgChart::Matchups),
+            "stages" => Ok(SvgChart::Stages),
+            "deaths" => Ok(SvgChart::Deaths),
+            _ => Err(CommandError::InvalidArgument(arg.to_string(), "chart")),
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CommandError::UnknownCommand(cmd) => write!(f, "Unrecognized command `{}`.", cmd),
+            CommandError::MissingArgument(kind) => write!(f, "Missing {}.", kind),
+            CommandError::InvalidArgument(arg, kind) => {
+                write!(f, "`{}` is not a valid {}.", arg, kind)
+            }
+            CommandError::InvalidName(e) => write!(f, "{}", e),
+            CommandError::InvalidConditions(e) => write!(f, "{}", e),
+        }
+    }
+}
@@ -0,0 +1,592 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::character::Character;
+use crate::command::{self, CommandError};
+use crate::filter::Filter;
+use crate::input;
+use crate::output::OutputFormat;
+use crate::parsable_enum::{self, Parsable, ParsableEnumError};
+use crate::stage::Stage;
+use crate::stats::Ranking;
+
+const APP_DIR: &str = "slippi_stats";
+const CONFIG_FILE: &str = "config.toml";
+const DEFAULT_PROFILE: &str = "default";
+//where versions before the config file saved the code and replay directory
+const LEGACY_LOCATION: &str = "data.cache";
+pub const ALIAS_KINDS: [&str; 2] = ["character", "stage"];
+//words the command line and interactive mode take besides `command::NAMES`
+const RESERVED_NAMES: [&str; 11] = [
+    "help",
+    "format",
+    "config",
+    "profile",
+    "change",
+    "end",
+    "interactive",
+    "tui",
+    "serve",
+    "run",
+    "output",
+];
+pub const KEYS: [&str; 6] = [
+    "code",
+    "dir",
+    "cache_dir",
+    "format",
+    "prior_games",
+    "prior_winrate",
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Config {
+    //the profile used unless another one is picked
+    #[serde(default = "default_profile")]
+    pub profile: String,
+    //saved commands run by name, like `weekly_fox = "character fox matchups since:7d format:table"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub queries: BTreeMap<String, String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    #[serde(skip)]
+    path: PathBuf,
+    //picked with --profile for a single run, without changing the saved one
+    #[serde(skip)]
+    current: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Profile {
+    pub code: Option<String>,
+    pub dir: Option<PathBuf>,
+    //defaults to a folder per replay directory under the user's cache directory
+    pub cache_dir: Option<PathBuf>,
+    pub format: Option<OutputFormat>,
+    //when set, best and worst are ranked by winrates shrunk towards prior_winrate (in percent)
+    //instead of the lower end of the confidence interval
+    pub prior_games: Option<f64>,
+    pub prior_winrate: Option<f64>,
+    //extra names by kind, like `[aliases.character]` with `cf = "captain falcon"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+//a `config` or `profile` command on one line, the same from the command line and interactive mode
+#[derive(Debug, PartialEq)]
+pub enum ConfigCommand {
+    Show,
+    Path,
+    Set(String, String),
+    Unset(String),
+    Alias(String, String, String),
+    Unalias(String, String),
+    Query(String, String),
+    Unquery(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ProfileCommand {
+    List,
+    Add(String, String, PathBuf),
+    Remove(String),
+    Switch(String),
+    Compare(String, String, Filter),
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    NoConfigDir,
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Write(PathBuf, io::Error),
+    Serialize(toml::ser::Error),
+    UnknownKey(String),
+    InvalidValue(String, String),
+    UnknownProfile(String),
+    ProfileExists(String),
+    UnknownAliasKind(String),
+    UnknownAlias(String),
+    InvalidAlias(String, ParsableEnumError),
+    ReservedName(String),
+    UnknownQuery(String),
+    InvalidQuery(String, CommandError),
+}
+
+#[derive(Deserialize)]
+struct LegacyLocation {
+    np_code: LegacyCode,
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct LegacyCode {
+    name: String,
+    num: usize,
+}
+
+impl Config {
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_DIR).join(CONFIG_FILE))
+    }
+
+    //reads the config at `path`, or the one in the user's config directory.
+    //a missing file gives an empty config, with the location from an old data.cache if there is one
+    pub fn load(path: Option<PathBuf>) -> Result<Self, ConfigError> {
+        let path = match path.or_else(Config::default_path) {
+            Some(path) => path,
+            None => return Err(ConfigError::NoConfigDir),
+        };
+        let mut config = match fs::read_to_string(&path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| ConfigError::Parse(path.clone(), e))?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Config::legacy(),
+            Err(e) => return Err(ConfigError::Read(path, e)),
+        };
+        config.path = path;
+        Ok(config)
+    }
+
+    fn legacy() -> Self {
+        let mut config = Config {
+            profile: default_profile(),
+            queries: BTreeMap::new(),
+            profiles: BTreeMap::new(),
+            path: PathBuf::new(),
+            current: None,
+        };
+        let legacy: Option<LegacyLocation> = fs::read_to_string(LEGACY_LOCATION)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok());
+        if let Some(legacy) = legacy {
+            let profile = config.active_mut();
+            profile.code = Some(format!("{}#{}", legacy.np_code.name, legacy.np_code.num));
+            profile.dir = Some(legacy.path);
+        }
+        config
+    }
+
+    pub fn save(&self) -> Result<(), ConfigError> {
+        let text = self.show()?;
+        let write = || -> io::Result<()> {
+            if let Some(parent) = self.path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&self.path, text)
+        };
+        write().map_err(|e| ConfigError::Write(self.path.clone(), e))
+    }
+
+    //the config as it's saved. paths toml can't hold, like ones that aren't UTF-8, are an error
+    pub fn show(&self) -> Result<String, ConfigError> {
+        toml::to_string_pretty(self).map_err(ConfigError::Serialize)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn active_name(&self) -> &str {
+        self.current.as_deref().unwrap_or(&self.profile)
+    }
+
+    pub fn active(&self) -> Profile {
+        self.profiles
+            .get(self.active_name())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn active_mut(&mut self) -> &mut Profile {
+        let name = self.active_name().to_string();
+        self.profiles.entry(name).or_default()
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&Profile, ConfigError> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| ConfigError::UnknownProfile(name.to_string()))
+    }
+
+    //uses `name` until the program exits
+    pub fn select(&mut self, name: &str) -> Result<(), ConfigError> {
+        self.profile(name)?;
+        self.current = Some(name.to_string());
+        Ok(())
+    }
+
+    //makes `name` the saved default, which is what the interactive mode loads
+    pub fn switch(&mut self, name: &str) -> Result<(), ConfigError> {
+        self.profile(name)?;
+        self.profile = name.to_string();
+        self.current = None;
+        Ok(())
+    }
+
+    pub fn add_profile(&mut self, name: &str, profile: Profile) -> Result<(), ConfigError> {
+        if self.profiles.contains_key(name) {
+            return Err(ConfigError::ProfileExists(name.to_string()));
+        }
+        self.profiles.insert(name.to_string(), profile);
+        Ok(())
+    }
+
+    //removing the active profile falls back to the first one left
+    pub fn remove_profile(&mut self, name: &str) -> Result<(), ConfigError> {
+        if self.profiles.remove(name).is_none() {
+            return Err(ConfigError::UnknownProfile(name.to_string()));
+        }
+        if self.current.as_deref() == Some(name) {
+            self.current = None;
+        }
+        if self.profile == name {
+            self.profile = match self.profiles.keys().next() {
+                Some(first) => first.clone(),
+                None => default_profile(),
+            };
+        }
+        Ok(())
+    }
+
+    //sets one of `KEYS` in the active profile from text, like `config set format table`
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = || ConfigError::InvalidValue(key.to_string(), value.to_string());
+        let profile = self.active_mut();
+        match key {
+            "code" => profile.code = Some(input::parse_code(value).ok_or_else(invalid)?),
+            "dir" => profile.dir = Some(PathBuf::from(value)),
+            "cache_dir" => profile.cache_dir = Some(PathBuf::from(value)),
+            "format" => profile.format = Some(value.to_lowercase().parse().map_err(|_| invalid())?),
+            "prior_games" => match value.parse::<f64>() {
+                Ok(games) if games >= 0.0 => profile.prior_games = Some(games),
+                _ => return Err(invalid()),
+            },
+            "prior_winrate" => match value.trim_end_matches('%').parse::<f64>() {
+                Ok(winrate) if (0.0..=100.0).contains(&winrate) => {
+                    profile.prior_winrate = Some(winrate)
+                }
+                _ => return Err(invalid()),
+            },
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+
+    //adds an alias to the active profile, checking that `name` is something it can point to
+    pub fn set_alias(&mut self, kind: &str, alias: &str, name: &str) -> Result<(), ConfigError> {
+        let mut aliases = BTreeMap::new();
+        aliases.insert(alias.to_lowercase(), name.to_string());
+        register(kind, &aliases)?;
+        self.active_mut()
+            .aliases
+            .entry(kind.to_string())
+            .or_default()
+            .extend(aliases);
+        Ok(())
+    }
+
+    pub fn remove_alias(&mut self, kind: &str, alias: &str) -> Result<(), ConfigError> {
+        let profile = self.active_mut();
+        let aliases = match profile.aliases.get_mut(kind) {
+            Some(aliases) => aliases,
+            None => return Err(ConfigError::UnknownAlias(alias.to_string())),
+        };
+        if aliases.remove(&alias.to_lowercase()).is_none() {
+            return Err(ConfigError::UnknownAlias(alias.to_string()));
+        }
+        if aliases.is_empty() {
+            profile.aliases.remove(kind);
+        }
+        profile.register_aliases()
+    }
+
+    //saves `query` under `name`, checking that it's a command that runs as is
+    pub fn set_query(&mut self, name: &str, query: &str) -> Result<(), ConfigError> {
+        let name = name.to_lowercase();
+        if command::NAMES.contains(&&name[..])
+            || RESERVED_NAMES.contains(&&name[..])
+            || name.contains(char::is_whitespace)
+        {
+            return Err(ConfigError::ReservedName(name));
+        }
+        let words: Vec<&str> = query.split_whitespace().collect();
+        command::parse(&words).map_err(|e| ConfigError::InvalidQuery(name.clone(), e))?;
+        self.queries.insert(name, words.join(" "));
+        Ok(())
+    }
+
+    pub fn remove_query(&mut self, name: &str) -> Result<(), ConfigError> {
+        match self.queries.remove(&name.to_lowercase()) {
+            Some(_) => Ok(()),
+            None => Err(ConfigError::UnknownQuery(name.to_string())),
+        }
+    }
+
+    //replaces a saved query's name at the start of `tokens` with its command, keeping any words
+    //after it so they can add to it, like `weekly_fox vs marth`
+    //saves the change a command makes. `show` and `path` don't change anything
+    pub fn run(&mut self, command: &ConfigCommand) -> Result<(), ConfigError> {
+        match command {
+            ConfigCommand::Show | ConfigCommand::Path => return Ok(()),
+            ConfigCommand::Set(key, value) => self.set(key, value),
+            ConfigCommand::Unset(key) => self.unset(key),
+            ConfigCommand::Alias(kind, alias, name) => self.set_alias(kind, alias, name),
+            ConfigCommand::Unalias(kind, alias) => self.remove_alias(kind, alias),
+            ConfigCommand::Query(name, query) => self.set_query(name, query),
+            ConfigCommand::Unquery(name) => self.remove_query(name),
+        }?;
+        self.save()
+    }
+
+    //saves the change a command makes. `list` and `compare` don't change anything
+    pub fn run_profile(&mut self, command: &ProfileCommand) -> Result<(), ConfigError> {
+        match command {
+            ProfileCommand::List | ProfileCommand::Compare(..) => return Ok(()),
+            ProfileCommand::Add(name, code, dir) => {
+                self.add_profile(name, Profile::new(code.clone(), dir.clone()))
+            }
+            ProfileCommand::Remove(name) => self.remove_profile(name),
+            ProfileCommand::Switch(name) => self.switch(name),
+        }?;
+        self.save()
+    }
+
+    pub fn expand_query(&self, tokens: &[&str]) -> Option<Vec<String>> {
+        let (name, rest) = tokens.split_first()?;
+        let query = self.queries.get(&name.to_lowercase())?;
+        let words = query.split_whitespace().chain(rest.iter().copied());
+        Some(words.map(String::from).collect())
+    }
+
+    pub fn unset(&mut self, key: &str) -> Result<(), ConfigError> {
+        let profile = self.active_mut();
+        match key {
+            "code" => profile.code = None,
+            "dir" => profile.dir = None,
+            "cache_dir" => profile.cache_dir = None,
+            "format" => profile.format = None,
+            "prior_games" => profile.prior_games = None,
+            "prior_winrate" => profile.prior_winrate = None,
+            _ => return Err(ConfigError::UnknownKey(key.to_string())),
+        }
+        Ok(())
+    }
+}
+
+impl Profile {
+    pub fn new(code: String, dir: PathBuf) -> Self {
+        Profile {
+            code: Some(code),
+            dir: Some(dir),
+            ..Profile::default()
+        }
+    }
+
+    //where the cache for `dir` goes. each replay directory gets its own folder so two
+    //directories with games for the same code don't mix
+    pub fn cache_dir(&self, dir: &Path) -> PathBuf {
+        if let Some(cache_dir) = &self.cache_dir {
+            return cache_dir.clone();
+        }
+        match dirs::cache_dir() {
+            Some(cache) => cache.join(APP_DIR).join(format!("{:016x}", path_hash(dir))),
+            None => dir.to_path_buf(),
+        }
+    }
+
+    //makes the profile's aliases the ones names are parsed with, replacing any from before
+    pub fn register_aliases(&self) -> Result<(), ConfigError> {
+        parsable_enum::clear_aliases();
+        for (kind, aliases) in &self.aliases {
+            register(kind, aliases)?;
+        }
+        Ok(())
+    }
+
+    pub fn ranking(&self) -> Ranking {
+        match self.prior_games {
+            Some(prior_games) => Ranking::Shrunk {
+                prior_games,
+                prior_winrate: self.prior_winrate.unwrap_or(50.0) / 100.0,
+            },
+            None => Ranking::LowerBound,
+        }
+    }
+}
+
+//fnv-1a, which unlike the std hasher is guaranteed to stay the same between rust versions
+impl ConfigCommand {
+    //the words after `config`. a missing argument is named so interactive mode can ask for it
+    pub fn parse(tokens: &[&str]) -> Result<Self, CommandError> {
+        let missing = |kind| Err(CommandError::MissingArgument(kind));
+        let (command, rest) = match tokens.split_first() {
+            Some((command, rest)) => (command.to_lowercase(), rest),
+            None => return Ok(ConfigCommand::Show),
+        };
+        match (&command[..], rest) {
+            ("show", []) => Ok(ConfigCommand::Show),
+            ("path", []) => Ok(ConfigCommand::Path),
+            ("set", []) | ("unset", []) => missing("config key"),
+            ("set", [_]) => missing("new value"),
+            ("set", [key, value @ ..]) => {
+                Ok(ConfigCommand::Set(key.to_lowercase(), value.join(" ")))
+            }
+            ("unset", [key]) => Ok(ConfigCommand::Unset(key.to_lowercase())),
+            ("alias", []) | ("unalias", []) => missing("alias kind"),
+            ("alias", [_]) | ("unalias", [_]) => missing("alias"),
+            ("alias", [_, _]) => missing("name it stands for"),
+            ("alias", [kind, alias, name @ ..]) => Ok(ConfigCommand::Alias(
+                kind.to_lowercase(),
+                alias.to_string(),
+                name.join(" "),
+            )),
+            ("unalias", [kind, alias]) => Ok(ConfigCommand::Unalias(
+                kind.to_lowercase(),
+                alias.to_string(),
+            )),
+            ("query", []) | ("unquery", []) => missing("query name"),
+            ("query", [_]) => missing("command to save"),
+            ("query", [name, query @ ..]) => {
+                Ok(ConfigCommand::Query(name.to_string(), query.join(" ")))
+            }
+            ("unquery", [name]) => Ok(ConfigCommand::Unquery(name.to_string())),
+            _ => Err(CommandError::InvalidArgument(
+                tokens.join(" "),
+                "config command",
+            )),
+        }
+    }
+}
+
+impl ProfileCommand {
+    //the words after `profile`, with missing arguments named like `ConfigCommand::parse`
+    pub fn parse(tokens: &[&str]) -> Result<Self, CommandError> {
+        let missing = |kind| Err(CommandError::MissingArgument(kind));
+        let (command, rest) = match tokens.split_first() {
+            Some((command, rest)) => (command.to_lowercase(), rest),
+            None => return Ok(ProfileCommand::List),
+        };
+        match (&command[..], rest) {
+            ("list", []) => Ok(ProfileCommand::List),
+            ("add", []) | ("remove", []) | ("switch", []) => missing("profile name"),
+            ("add", [_]) => missing("netplay code"),
+            ("add", [_, _]) => missing("replay directory"),
+            ("add", [name, code, dir @ ..]) => {
+                let code = input::parse_code(code).ok_or_else(|| {
+                    CommandError::InvalidArgument(code.to_string(), "netplay code")
+                })?;
+                Ok(ProfileCommand::Add(
+                    name.to_string(),
+                    code,
+                    PathBuf::from(dir.join(" ")),
+                ))
+            }
+            ("remove", [name]) => Ok(ProfileCommand::Remove(name.to_string())),
+            ("switch", [name]) => Ok(ProfileCommand::Switch(name.to_string())),
+            ("compare", []) => missing("first profile name"),
+            ("compare", [_]) => missing("second profile name"),
+            ("compare", [first, second, conditions @ ..]) => {
+                let filter = if conditions.is_empty() {
+                    Filter::new()
+                } else {
+                    let conditions = conditions.join(" ").to_lowercase();
+                    conditions
+                        .parse()
+                        .map_err(CommandError::InvalidConditions)?
+                };
+                Ok(ProfileCommand::Compare(
+                    first.to_string(),
+                    second.to_string(),
+                    filter,
+                ))
+            }
+            _ => Err(CommandError::InvalidArgument(
+                tokens.join(" "),
+                "profile command",
+            )),
+        }
+    }
+}
+
+fn path_hash(path: &Path) -> u64 {
+    path.to_string_lossy()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+fn register(kind: &str, aliases: &BTreeMap<String, String>) -> Result<(), ConfigError> {
+    match kind {
+        "character" => register_kind::<Character>(aliases),
+        "stage" => register_kind::<Stage>(aliases),
+        _ => Err(ConfigError::UnknownAliasKind(kind.to_string())),
+    }
+}
+
+fn register_kind<T: Parsable>(aliases: &BTreeMap<String, String>) -> Result<(), ConfigError> {
+    for (alias, name) in aliases {
+        let value = parsable_enum::parse::<T>(name)
+            .map_err(|e| ConfigError::InvalidAlias(alias.clone(), e))?;
+        parsable_enum::add_alias(alias, value);
+    }
+    Ok(())
+}
+
+fn default_profile() -> String {
+    String::from(DEFAULT_PROFILE)
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.code, &self.dir) {
+            (Some(code), Some(dir)) => write!(f, "{} in {:?}", code, dir),
+            (Some(code), None) => write!(f, "{}, no replay directory", code),
+            (None, _) => write!(f, "no code set"),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::NoConfigDir => write!(
+                f,
+                "Couldn't find a config directory for this system, pass `--config` instead."
+            ),
+            ConfigError::Read(path, e) => write!(f, "Couldn't read config {:?}: {}", path, e),
+            ConfigError::Parse(path, e) => write!(f, "Couldn't parse config {:?}: {}", path, e),
+            ConfigError::Write(path, e) => write!(f, "Couldn't save config {:?}: {}", path, e),
+            ConfigError::Serialize(e) => write!(f, "Couldn't write out the config: {}", e),
+            ConfigError::UnknownKey(key) => write!(
+                f,
+                "Unknown config key `{}`, use one of {}.",
+                key,
+                KEYS.join(", ")
+            ),
+            ConfigError::InvalidValue(key, value) => {
+                write!(f, "`{}` is not a valid value for `{}`.", value, key)
+            }
+            ConfigError::UnknownProfile(name) => write!(f, "There is no profile named `{}`.", name),
+            ConfigError::ProfileExists(name) => {
+                write!(f, "There is already a profile named `{}`.", name)
+            }
+            ConfigError::UnknownAliasKind(kind) => write!(
+                f,
+                "Aliases can't be added for `{}`, use one of {}.",
+                kind,
+                ALIAS_KINDS.join(", ")
+            ),
+            ConfigError::UnknownAlias(alias) => write!(f, "There is no alias `{}`.", alias),
+            ConfigError::InvalidAlias(alias, e) => write!(f, "Alias `{}`: {}", alias, e),
+            ConfigError::ReservedName(name) => {
+                write!(f, "`{}` can't be used as the name of a query.", name)
+            }
+            ConfigError::UnknownQuery(name) => write!(f, "There is no saved query `{}`.", name),
+            ConfigError::InvalidQuery(name, e) => write!(f, "Query `{}`: {}", name, e),
+        }
+    }
+}
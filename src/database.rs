@@ -0,0 +1,218 @@
+use rusqlite::types::ValueRef;
+use rusqlite::{params, Connection, Statement};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::gamedata::{move_name, GameData, MatchResult, MoveUsage};
+use crate::playerdata::PlayerData;
+use crate::report::QueryReport;
+
+//dates are stored in UTC as `YYYY-MM-DD HH:MM:SS` so sqlite's date functions work on them
+const SCHEMA: &str = "
+CREATE TABLE players (
+    id INTEGER PRIMARY KEY,
+    code TEXT NOT NULL UNIQUE
+);
+CREATE TABLE games (
+    id INTEGER PRIMARY KEY,
+    played_at TEXT NOT NULL,
+    stage TEXT NOT NULL,
+    player_id INTEGER NOT NULL REFERENCES players(id),
+    player_character TEXT NOT NULL,
+    opponent_id INTEGER NOT NULL REFERENCES players(id),
+    opponent_character TEXT NOT NULL,
+    -- 'win', 'loss', 'tie' or 'quit', from the point of view of player_id
+    result TEXT NOT NULL,
+    -- 'stocks' or 'timeout', null for ties and quits
+    end_type TEXT,
+    -- the port that quit out, null unless the result is 'quit'
+    quit_port INTEGER
+);
+CREATE TABLE stocks (
+    game_id INTEGER NOT NULL REFERENCES games(id),
+    player_id INTEGER NOT NULL REFERENCES players(id),
+    -- 1 for the first stock that player lost in the game
+    stock INTEGER NOT NULL,
+    percent REAL NOT NULL,
+    PRIMARY KEY (game_id, player_id, stock)
+);
+CREATE TABLE moves (
+    game_id INTEGER NOT NULL REFERENCES games(id),
+    player_id INTEGER NOT NULL REFERENCES players(id),
+    attack_id INTEGER NOT NULL,
+    move TEXT NOT NULL,
+    hits INTEGER NOT NULL,
+    damage REAL NOT NULL,
+    PRIMARY KEY (game_id, player_id, attack_id)
+);
+CREATE INDEX games_played_at ON games (played_at);
+";
+
+struct Inserts<'a> {
+    player: Statement<'a>,
+    game: Statement<'a>,
+    stock: Statement<'a>,
+    moves: Statement<'a>,
+    player_ids: HashMap<String, i64>,
+}
+
+//the first bytes of every sqlite database file
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+//replaces an earlier export at `path` with a fresh database. the database is written next to it
+//first, so a failed export leaves the old one alone. other files are never overwritten
+pub fn export(data: &PlayerData, path: &Path) -> io::Result<()> {
+    if path.exists() && !is_database(path)? {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{:?} isn't a database, not overwriting it", path),
+        ));
+    }
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    let temp = path.with_file_name(name);
+    if temp.exists() {
+        fs::remove_file(&temp)?;
+    }
+    let written = Connection::open(&temp).and_then(|mut conn| {
+        write(&mut conn, data)?;
+        conn.close().map_err(|(_, e)| e)
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp);
+        return Err(io::Error::other(e));
+    }
+    fs::rename(&temp, path)
+}
+
+fn is_database(path: &Path) -> io::Result<bool> {
+    let mut header = [0; SQLITE_HEADER.len()];
+    let mut file = File::open(path)?;
+    match file.read_exact(&mut header) {
+        Ok(()) => Ok(header == SQLITE_HEADER),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(fs::metadata(path)?.len() == 0),
+        Err(e) => Err(e),
+    }
+}
+
+//runs a query against an in memory copy of the database
+pub fn query(data: &PlayerData, sql: &str) -> rusqlite::Result<QueryReport> {
+    let mut conn = Connection::open_in_memory()?;
+    write(&mut conn, data)?;
+    let mut statement = conn.prepare(sql)?;
+    let headers: Vec<String> = statement
+        .column_names()
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+    let mut rows = statement.query([])?;
+    let mut report = QueryReport {
+        headers,
+        rows: Vec::new(),
+    };
+    while let Some(row) = rows.next()? {
+        let cells = (0..report.headers.len())
+            .map(|i| row.get_ref(i).map(cell_text))
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        report.rows.push(cells);
+    }
+    Ok(report)
+}
+
+fn write(conn: &mut Connection, data: &PlayerData) -> rusqlite::Result<()> {
+    conn.execute_batch(SCHEMA)?;
+    let tx = conn.transaction()?;
+    {
+        let mut inserts = Inserts {
+            player: tx.prepare("INSERT INTO players (code) VALUES (?1)")?,
+            game: tx.prepare(
+                "INSERT INTO games (played_at, stage, player_id, player_character, opponent_id, \
+                 opponent_character, result, end_type, quit_port) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            )?,
+            stock: tx.prepare(
+                "INSERT INTO stocks (game_id, player_id, stock, percent) VALUES (?1, ?2, ?3, ?4)",
+            )?,
+            moves: tx.prepare(
+                "INSERT INTO moves (game_id, player_id, attack_id, move, hits, damage) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?,
+            player_ids: HashMap::new(),
+        };
+        let player_id = inserts.player_id(data.code())?;
+        for game in data.games() {
+            inserts.game(game, player_id)?;
+        }
+    }
+    tx.commit()
+}
+
+impl Inserts<'_> {
+    fn player_id(&mut self, code: &str) -> rusqlite::Result<i64> {
+        if let Some(&id) = self.player_ids.get(code) {
+            return Ok(id);
+        }
+        let id = self.player.insert(params![code])?;
+        self.player_ids.insert(code.to_string(), id);
+        Ok(id)
+    }
+
+    fn game(&mut self, game: &GameData, player_id: i64) -> rusqlite::Result<()> {
+        let opponent_id = self.player_id(&game.opponent_code)?;
+        let (result, end_type, quit_port) = match &game.match_result {
+            MatchResult::Victory(end) => ("win", Some(format!("{}", end)), None),
+            MatchResult::Loss(end) => ("loss", Some(format!("{}", end)), None),
+            MatchResult::Tie => ("tie", None, None),
+            MatchResult::EarlyEnd(port) => ("quit", None, Some(*port as i64)),
+        };
+        let game_id = self.game.insert(params![
+            game.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
+            format!("{}", game.stage),
+            player_id,
+            format!("{}", game.player_char),
+            opponent_id,
+            format!("{}", game.opponent_char),
+            result,
+            end_type,
+            quit_port,
+        ])?;
+        self.stocks(game_id, player_id, &game.death_percents)?;
+        self.stocks(game_id, opponent_id, &game.kill_percents)?;
+        self.moves(game_id, player_id, &game.moves)?;
+        self.moves(game_id, opponent_id, &game.opponent_moves)
+    }
+
+    fn stocks(&mut self, game_id: i64, player_id: i64, percents: &[f32]) -> rusqlite::Result<()> {
+        for (i, percent) in percents.iter().enumerate() {
+            self.stock
+                .execute(params![game_id, player_id, i as i64 + 1, *percent as f64])?;
+        }
+        Ok(())
+    }
+
+    fn moves(&mut self, game_id: i64, player_id: i64, moves: &[MoveUsage]) -> rusqlite::Result<()> {
+        for usage in moves {
+            self.moves.execute(params![
+                game_id,
+                player_id,
+                usage.attack,
+                move_name(usage.attack),
+                usage.hits as i64,
+                usage.damage as f64,
+            ])?;
+        }
+        Ok(())
+    }
+}
+
+fn cell_text(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => String::new(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned(),
+        ValueRef::Blob(b) => format!("<{} bytes>", b.len()),
+    }
+}
@@ -0,0 +1,158 @@
+use serde::Serialize;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::gamedata::{GameData, FIRST_FRAME};
+use crate::report::StockLoss;
+
+//how much of a clip comes before and after the stock is lost, at 60 frames a second
+const CLIP_LEAD_FRAMES: i32 = 60 * 8;
+const CLIP_TAIL_FRAMES: i32 = 60 * 2;
+
+//which games or moments to queue. without `clips` every game is queued whole
+#[derive(Debug, Default, PartialEq)]
+pub struct Queue {
+    //the game's number among the ones matching the conditions
+    pub game: Option<usize>,
+    pub clips: Option<ClipStocks>,
+    //only stocks lost under or over this percent
+    pub below: Option<f32>,
+    pub above: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClipStocks {
+    //stocks the player lost
+    Deaths,
+    //stocks the player took
+    Kills,
+}
+
+#[derive(Debug)]
+pub struct ClipStocksParseError(String);
+
+//the playback file slippi dolphin takes with `-i`, see the comm spec in slippi's dolphin repo
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PlaybackFile {
+    mode: &'static str,
+    replay: &'static str,
+    is_real_time_mode: bool,
+    output_overlay_files: bool,
+    queue: Vec<Entry>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start_frame: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end_frame: Option<i32>,
+}
+
+//queues `games` oldest first and returns how many entries were written. clips need every frame of
+//the replay again, replays that can't be read for them are skipped with a message
+pub fn export(games: &[&GameData], queue: &Queue, np_code: &str, path: &Path) -> io::Result<usize> {
+    let mut games: Vec<&GameData> = games
+        .iter()
+        .copied()
+        .filter(|g| !g.path.as_os_str().is_empty())
+        .collect();
+    games.sort_by_key(|g| g.timestamp);
+    let mut entries = Vec::new();
+    for game in games {
+        let replay = absolute(&game.path);
+        let stocks = match queue.clips {
+            Some(stocks) => stocks,
+            None => {
+                entries.push(Entry {
+                    path: replay,
+                    start_frame: None,
+                    end_frame: None,
+                });
+                continue;
+            }
+        };
+        let detail = match GameData::detail(&game.path, np_code) {
+            Ok(detail) => detail,
+            Err(e) => {
+                eprintln!("Skipping {:?}: {:?}", game.path, e);
+                continue;
+            }
+        };
+        let clips = clip_frames(&detail.stocks, stocks, queue, np_code, game.frames);
+        entries.extend(clips.into_iter().map(|(start, end)| Entry {
+            path: replay.clone(),
+            start_frame: Some(start),
+            end_frame: Some(end),
+        }));
+    }
+    let count = entries.len();
+    let playback = PlaybackFile {
+        mode: "queue",
+        replay: "",
+        is_real_time_mode: false,
+        output_overlay_files: true,
+        queue: entries,
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut out = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut out, &playback)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(count)
+}
+
+//the first and last frame of a clip for each stock in `stocks` the queue asks for, kept inside
+//the replay's `frames`
+pub fn clip_frames(
+    stocks: &[StockLoss],
+    clips: ClipStocks,
+    queue: &Queue,
+    np_code: &str,
+    frames: usize,
+) -> Vec<(i32, i32)> {
+    let last_frame = FIRST_FRAME + frames as i32 - 1;
+    stocks
+        .iter()
+        .filter(|stock| (stock.lost_by == np_code) == (clips == ClipStocks::Deaths))
+        .filter(|stock| queue.below.is_none_or(|below| stock.percent < below))
+        .filter(|stock| queue.above.is_none_or(|above| stock.percent > above))
+        .map(|stock| {
+            let frame = FIRST_FRAME + stock.frame as i32;
+            (
+                (frame - CLIP_LEAD_FRAMES).max(FIRST_FRAME),
+                (frame + CLIP_TAIL_FRAMES).min(last_frame),
+            )
+        })
+        .collect()
+}
+
+//dolphin is started from its own folder, so relative replay paths wouldn't be found
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+impl FromStr for ClipStocks {
+    type Err = ClipStocksParseError;
+    fn from_str(arg: &str) -> Result<Self, Self::Err> {
+        match arg {
+            "deaths" => Ok(ClipStocks::Deaths),
+            "kills" => Ok(ClipStocks::Kills),
+            _ => Err(ClipStocksParseError(arg.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for ClipStocksParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` is not a clip type. Use deaths or kills.", self.0)
+    }
+}
@@ -0,0 +1,274 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::character::Character;
+use crate::gamedata::{GameData, MatchResult};
+use crate::parsable_enum::{GameDataCondition, ParsableEnumError};
+use crate::stage::Stage;
+
+//a set of conditions a game has to meet. anything left as `None` matches every game
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Filter {
+    pub player_char: Option<Character>,
+    pub opponent_char: Option<Character>,
+    pub stage: Option<Stage>,
+    pub opponent_code: Option<String>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    //ties and games that were quit out of are neither
+    pub result: Option<Outcome>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Win,
+    Loss,
+}
+
+#[derive(Debug)]
+pub enum FilterParseError {
+    InvalidName(ParsableEnumError),
+    InvalidDate(String),
+    MissingValue(String),
+    InvalidResult(String),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FilterKey {
+    PlayerChar,
+    OpponentChar,
+    Stage,
+    OpponentCode,
+    Since,
+    Until,
+    Result,
+}
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    //sets a condition using any of the keywords the text syntax accepts, like `opp` or `since`.
+    //returns false if the keyword isn't one of them
+    pub fn set_keyword(&mut self, keyword: &str, value: &str) -> Result<bool, FilterParseError> {
+        match FilterKey::from_keyword(keyword) {
+            Some(key) => self.set(key, value).map(|_| true),
+            None => Ok(false),
+        }
+    }
+
+    pub fn is_keyword(word: &str) -> bool {
+        FilterKey::from_keyword(word).is_some()
+    }
+
+    fn set(&mut self, key: FilterKey, value: &str) -> Result<(), FilterParseError> {
+        match key {
+            FilterKey::PlayerChar => {
+                self.player_char = Some(value.parse().map_err(FilterParseError::InvalidName)?)
+            }
+            FilterKey::OpponentChar => {
+                self.opponent_char = Some(value.parse().map_err(FilterParseError::InvalidName)?)
+            }
+            FilterKey::Stage => {
+                self.stage = Some(value.parse().map_err(FilterParseError::InvalidName)?)
+            }
+            FilterKey::OpponentCode => self.opponent_code = Some(value.to_uppercase()),
+            FilterKey::Since => self.since = Some(parse_date(value, Utc::now())?),
+            FilterKey::Until => self.until = Some(parse_date(value, Utc::now())?),
+            FilterKey::Result => {
+                self.result = Some(match value {
+                    "win" | "wins" | "won" => Outcome::Win,
+                    "loss" | "losses" | "lost" => Outcome::Loss,
+                    _ => return Err(FilterParseError::InvalidResult(value.to_string())),
+                })
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FilterKey {
+    fn from_keyword(word: &str) -> Option<Self> {
+        match word {
+            "as" | "char" | "character" => Some(FilterKey::PlayerChar),
+            "vs" | "vs." | "opp" | "opponent" => Some(FilterKey::OpponentChar),
+            "on" | "stage" => Some(FilterKey::Stage),
+            "code" | "against" => Some(FilterKey::OpponentCode),
+            "since" | "after" | "from" => Some(FilterKey::Since),
+            "until" | "before" | "to" => Some(FilterKey::Until),
+            "result" => Some(FilterKey::Result),
+            _ => None,
+        }
+    }
+}
+
+//accepts `2021-03-01`, `2021-03`, a month name like `march` (the most recent one that has started),
+//or a relative time like `30d`, `2w` or `6m`
+pub fn parse_date(arg: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, FilterParseError> {
+    let invalid = || FilterParseError::InvalidDate(arg.to_string());
+    let midnight = |date: NaiveDate| Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap());
+    if let Ok(date) = NaiveDate::parse_from_str(arg, "%Y-%m-%d") {
+        return Ok(midnight(date));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&format!("{}-01", arg), "%Y-%m-%d") {
+        return Ok(midnight(date));
+    }
+    if let Some(month) = MONTHS
+        .iter()
+        .position(|m| arg.len() >= 3 && m.starts_with(arg))
+    {
+        let month = month as u32 + 1;
+        let year = if month > now.month() {
+            now.year() - 1
+        } else {
+            now.year()
+        };
+        return Ok(midnight(NaiveDate::from_ymd_opt(year, month, 1).unwrap()));
+    }
+    let (i, unit) = arg.char_indices().last().ok_or_else(invalid)?;
+    let num: i64 = arg[..i].parse().map_err(|_| invalid())?;
+    let days_per_unit = match unit {
+        'd' => 1,
+        'w' => 7,
+        'm' => 30,
+        'y' => 365,
+        _ => return Err(invalid()),
+    };
+    //anything too far back for a date is as invalid as a typo
+    num.checked_mul(days_per_unit)
+        .and_then(Duration::try_days)
+        .and_then(|duration| now.checked_sub_signed(duration))
+        .ok_or_else(invalid)
+}
+
+impl GameDataCondition for Filter {
+    fn game_data_condition(&self, game: &GameData) -> bool {
+        self.player_char.is_none_or(|c| game.player_char == c)
+            && self.opponent_char.is_none_or(|c| game.opponent_char == c)
+            && self.stage.is_none_or(|s| game.stage == s)
+            && self
+                .opponent_code
+                .as_ref()
+                .is_none_or(|code| &game.opponent_code == code)
+            && self.since.is_none_or(|since| game.timestamp >= since)
+            && self.until.is_none_or(|until| game.timestamp < until)
+            && self.result.is_none_or(|result| match result {
+                Outcome::Win => matches!(game.match_result, MatchResult::Victory(_)),
+                Outcome::Loss => matches!(game.match_result, MatchResult::Loss(_)),
+            })
+    }
+}
+
+//parses things like `fox vs marth on fd before march`. words before any keyword are the player's
+//character, and `key:value` works the same as `key value`
+impl FromStr for Filter {
+    type Err = FilterParseError;
+    fn from_str(arg: &str) -> Result<Self, Self::Err> {
+        let mut filter = Filter::new();
+        let mut key = FilterKey::PlayerChar;
+        let mut value: Vec<&str> = Vec::new();
+        //only `None` for the leading words, which are allowed to be empty
+        let mut keyword: Option<String> = None;
+        for word in arg.split_whitespace() {
+            let (word_keyword, word_key, rest) = match word.split_once(':') {
+                Some((k, rest)) => (k, FilterKey::from_keyword(k), rest),
+                None => (word, FilterKey::from_keyword(word), ""),
+            };
+            match word_key {
+                Some(new_key) => {
+                    if !value.is_empty() {
+                        filter.set(key, &value.join(" "))?;
+                    } else if let Some(k) = keyword {
+                        return Err(FilterParseError::MissingValue(k));
+                    }
+                    key = new_key;
+                    keyword = Some(word_keyword.to_string());
+                    value.clear();
+                    if !rest.is_empty() {
+                        value.push(rest);
+                    }
+                }
+                None => value.push(word),
+            }
+        }
+        if !value.is_empty() {
+            filter.set(key, &value.join(" "))?;
+        } else if let Some(k) = keyword {
+            return Err(FilterParseError::MissingValue(k));
+        }
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(c) = self.player_char {
+            parts.push(format!("{}", c));
+        }
+        if let Some(c) = self.opponent_char {
+            parts.push(format!("vs. {}", c));
+        }
+        if let Some(s) = self.stage {
+            parts.push(format!("on {}", s));
+        }
+        if let Some(code) = &self.opponent_code {
+            parts.push(format!("against {}", code));
+        }
+        if let Some(since) = self.since {
+            parts.push(format!("since {}", since.format("%Y-%m-%d")));
+        }
+        if let Some(until) = self.until {
+            parts.push(format!("before {}", until.format("%Y-%m-%d")));
+        }
+        match self.result {
+            Some(Outcome::Win) => parts.push(String::from("result win")),
+            Some(Outcome::Loss) => parts.push(String::from("result loss")),
+            None => (),
+        }
+        if parts.is_empty() {
+            return write!(f, "All games");
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl fmt::Display for FilterParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FilterParseError::InvalidName(e) => write!(f, "{}", e),
+            FilterParseError::InvalidDate(d) => write!(
+                f,
+                "`{}` is not a date. Use YYYY-MM-DD, YYYY-MM, a month name, or something like 30d.",
+                d
+            ),
+            FilterParseError::MissingValue(k) => write!(f, "`{}` needs a value after it.", k),
+            FilterParseError::InvalidResult(r) => {
+                write!(f, "`{}` is not a result. Use win or loss.", r)
+            }
+        }
+    }
+}
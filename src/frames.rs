@@ -0,0 +1,311 @@
+use parquet::basic::Compression;
+use parquet::data_type::{BoolType, ByteArray, ByteArrayType, FloatType, Int32Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use peppi::frame::Frame;
+use peppi::game::{Frames, Game, Port};
+use std::convert::TryFrom;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::character::Character;
+use crate::gamedata::GameData;
+use crate::output::csv_escape;
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Text,
+    Int,
+    Float,
+    OptionalFloat,
+    OptionalBool,
+}
+
+//one row for each port on each frame, the same for every replay. frames start at -123, the first
+//frame of the countdown. ice climbers' nana isn't included. the optional columns are empty for
+//replays from slippi versions before they were recorded
+const COLUMNS: [(&str, Kind); 20] = [
+    ("replay", Kind::Text),
+    ("frame", Kind::Int),
+    ("port", Kind::Int),
+    ("code", Kind::Text),
+    ("character", Kind::Text),
+    ("x", Kind::Float),
+    ("y", Kind::Float),
+    ("action_state", Kind::Int),
+    ("state_age", Kind::OptionalFloat),
+    ("percent", Kind::Float),
+    ("stocks", Kind::Int),
+    ("shield", Kind::Float),
+    ("airborne", Kind::OptionalBool),
+    ("hitlag", Kind::OptionalFloat),
+    ("joystick_x", Kind::Float),
+    ("joystick_y", Kind::Float),
+    ("cstick_x", Kind::Float),
+    ("cstick_y", Kind::Float),
+    ("trigger", Kind::Float),
+    ("buttons", Kind::Int),
+];
+
+enum Value {
+    Text(String),
+    Int(i32),
+    Float(f32),
+    Bool(bool),
+    Null,
+}
+
+type Row = Vec<Value>;
+
+struct PortInfo {
+    port: i32,
+    code: String,
+    character: String,
+}
+
+//csv unless the file ends in `.parquet`
+enum Sink {
+    Csv(BufWriter<File>),
+    Parquet(SerializedFileWriter<File>),
+}
+
+//writes the frames of every game in `games` that still has its replay, one parquet row group per game.
+//replays that can't be read are skipped with a message. returns how many games were written
+pub fn export(games: &[&GameData], path: &Path) -> io::Result<usize> {
+    let mut sink = Sink::create(path)?;
+    let mut written = 0;
+    for game in games {
+        if game.path.as_os_str().is_empty() {
+            continue;
+        }
+        let replay = match read(&game.path) {
+            Ok(replay) => replay,
+            Err(e) => {
+                eprintln!("Skipping {:?}: {}", game.path, e);
+                continue;
+            }
+        };
+        sink.write(&rows(&replay, &game.path.display().to_string()))?;
+        written += 1;
+    }
+    sink.close()?;
+    Ok(written)
+}
+
+fn read(path: &Path) -> io::Result<Game> {
+    if !path.is_file() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "replay not found"));
+    }
+    GameData::get_game_data(path, false).map_err(|e| io::Error::other(format!("{:?}", e)))
+}
+
+fn rows(game: &Game, replay: &str) -> Vec<Row> {
+    let netplay = game.metadata.players.as_deref().unwrap_or(&[]);
+    let ports: Vec<PortInfo> = game
+        .start
+        .players
+        .iter()
+        .enumerate()
+        .map(|(i, player)| PortInfo {
+            port: port_number(player.port),
+            code: netplay
+                .get(i)
+                .and_then(|p| p.netplay.as_ref())
+                .map_or(String::new(), |n| n.code.clone()),
+            character: Character::try_from(player.character.0 as usize)
+                .map_or(String::new(), |c| c.to_string()),
+        })
+        .collect();
+    match &game.frames {
+        Frames::P1(frames) => port_rows(frames, &ports, replay),
+        Frames::P2(frames) => port_rows(frames, &ports, replay),
+        Frames::P3(frames) => port_rows(frames, &ports, replay),
+        Frames::P4(frames) => port_rows(frames, &ports, replay),
+    }
+}
+
+fn port_rows<const N: usize>(frames: &[Frame<N>], ports: &[PortInfo], replay: &str) -> Vec<Row> {
+    let mut rows = Vec::with_capacity(frames.len() * N);
+    for frame in frames {
+        for (data, info) in frame.ports.iter().zip(ports) {
+            let (pre, post) = (&data.leader.pre, &data.leader.post);
+            rows.push(vec![
+                Value::Text(replay.to_string()),
+                Value::Int(frame.index),
+                Value::Int(info.port),
+                Value::Text(info.code.clone()),
+                Value::Text(info.character.clone()),
+                Value::Float(post.position.x),
+                Value::Float(post.position.y),
+                Value::Int(u16::from(post.state) as i32),
+                post.state_age.map_or(Value::Null, Value::Float),
+                Value::Float(post.damage),
+                Value::Int(post.stocks as i32),
+                Value::Float(post.shield),
+                post.airborne.map_or(Value::Null, Value::Bool),
+                post.hitlag.map_or(Value::Null, Value::Float),
+                Value::Float(pre.joystick.x),
+                Value::Float(pre.joystick.y),
+                Value::Float(pre.cstick.x),
+                Value::Float(pre.cstick.y),
+                Value::Float(pre.triggers),
+                Value::Int(pre.buttons.physical.0 as i32),
+            ]);
+        }
+    }
+    rows
+}
+
+fn port_number(port: Port) -> i32 {
+    match port {
+        Port::P1 => 1,
+        Port::P2 => 2,
+        Port::P3 => 3,
+        Port::P4 => 4,
+    }
+}
+
+fn schema() -> String {
+    let fields: Vec<String> = COLUMNS
+        .iter()
+        .map(|(name, kind)| match kind {
+            Kind::Text => format!("REQUIRED BYTE_ARRAY {} (UTF8);", name),
+            Kind::Int => format!("REQUIRED INT32 {};", name),
+            Kind::Float => format!("REQUIRED FLOAT {};", name),
+            Kind::OptionalFloat => format!("OPTIONAL FLOAT {};", name),
+            Kind::OptionalBool => format!("OPTIONAL BOOLEAN {};", name),
+        })
+        .collect();
+    format!("message frames {{ {} }}", fields.join(" "))
+}
+
+impl Sink {
+    fn create(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = File::create(path)?;
+        let parquet = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("parquet"));
+        if !parquet {
+            let mut out = BufWriter::new(file);
+            let names: Vec<&str> = COLUMNS.iter().map(|(name, _)| *name).collect();
+            writeln!(out, "{}", names.join(","))?;
+            return Ok(Sink::Csv(out));
+        }
+        let schema = Arc::new(parse_message_type(&schema()).map_err(io::Error::other)?);
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        SerializedFileWriter::new(file, schema, Arc::new(properties))
+            .map(Sink::Parquet)
+            .map_err(io::Error::other)
+    }
+
+    fn write(&mut self, rows: &[Row]) -> io::Result<()> {
+        match self {
+            Sink::Csv(out) => {
+                for row in rows {
+                    let cells: Vec<String> = row.iter().map(csv_cell).collect();
+                    writeln!(out, "{}", cells.join(","))?;
+                }
+                Ok(())
+            }
+            Sink::Parquet(writer) => write_row_group(writer, rows).map_err(io::Error::other),
+        }
+    }
+
+    fn close(self) -> io::Result<()> {
+        match self {
+            Sink::Csv(mut out) => out.flush(),
+            Sink::Parquet(writer) => writer.close().map(|_| ()).map_err(io::Error::other),
+        }
+    }
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Text(text) => csv_escape(text),
+        Value::Int(int) => int.to_string(),
+        Value::Float(float) => float.to_string(),
+        Value::Bool(bool) => bool.to_string(),
+        Value::Null => String::new(),
+    }
+}
+
+fn write_row_group(
+    writer: &mut SerializedFileWriter<File>,
+    rows: &[Row],
+) -> parquet::errors::Result<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+    let mut row_group = writer.next_row_group()?;
+    let mut i = 0;
+    while let Some(mut column) = row_group.next_column()? {
+        let values = rows.iter().map(|row| &row[i]);
+        //nulls are left out of the values and marked with a definition level of 0
+        let levels: Vec<i16> = values
+            .clone()
+            .map(|v| !matches!(v, Value::Null) as i16)
+            .collect();
+        let levels = match COLUMNS[i].1 {
+            Kind::OptionalFloat | Kind::OptionalBool => Some(&levels[..]),
+            _ => None,
+        };
+        match COLUMNS[i].1 {
+            Kind::Text => {
+                let texts: Vec<ByteArray> = values
+                    .filter_map(|v| match v {
+                        Value::Text(text) => Some(ByteArray::from(text.as_str())),
+                        _ => None,
+                    })
+                    .collect();
+                column
+                    .typed::<ByteArrayType>()
+                    .write_batch(&texts, levels, None)?;
+            }
+            Kind::Int => {
+                let ints: Vec<i32> = values
+                    .filter_map(|v| match v {
+                        Value::Int(int) => Some(*int),
+                        _ => None,
+                    })
+                    .collect();
+                column
+                    .typed::<Int32Type>()
+                    .write_batch(&ints, levels, None)?;
+            }
+            Kind::Float | Kind::OptionalFloat => {
+                let floats: Vec<f32> = values
+                    .filter_map(|v| match v {
+                        Value::Float(float) => Some(*float),
+                        _ => None,
+                    })
+                    .collect();
+                column
+                    .typed::<FloatType>()
+                    .write_batch(&floats, levels, None)?;
+            }
+            Kind::OptionalBool => {
+                let bools: Vec<bool> = values
+                    .filter_map(|v| match v {
+                        Value::Bool(bool) => Some(*bool),
+                        _ => None,
+                    })
+                    .collect();
+                column
+                    .typed::<BoolType>()
+                    .write_batch(&bools, levels, None)?;
+            }
+        }
+        column.close()?;
+        i += 1;
+    }
+    row_group.close()?;
+    Ok(())
+}
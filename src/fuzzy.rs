@@ -0,0 +1,85 @@
+//matching typed names against a list of known ones, for names of characters, stages and the like
+
+const MAX_SUGGESTIONS: usize = 3;
+
+pub enum Match<T> {
+    Found(T),
+    //nothing close enough to pick on its own, with the closest values first
+    NotFound(Vec<T>),
+}
+
+//`names` should be lowercase. picks, in order: an exact name, the only value with a name (or a word
+//in a name) starting with `input`, or the only value within a typo or two of `input`
+pub fn find<T: Copy + PartialEq>(input: &str, names: &[(String, T)]) -> Match<T> {
+    let input = input.trim().to_lowercase();
+    if let Some((_, value)) = names.iter().find(|(name, _)| *name == input) {
+        return Match::Found(*value);
+    }
+
+    let mut prefixed = Vec::new();
+    if input.chars().count() >= 2 {
+        for (name, value) in names {
+            let starts = name.starts_with(&input) || name.split(' ').any(|w| w.starts_with(&input));
+            if starts && !prefixed.contains(value) {
+                prefixed.push(*value);
+            }
+        }
+    }
+    if let [value] = prefixed[..] {
+        return Match::Found(value);
+    }
+
+    //the smallest distance to any name of each value
+    let mut closest: Vec<(usize, T)> = Vec::new();
+    for (name, value) in names {
+        let d = distance(&input, name);
+        match closest.iter_mut().find(|(_, v)| v == value) {
+            Some(entry) => entry.0 = entry.0.min(d),
+            None => closest.push((d, *value)),
+        }
+    }
+    closest.sort_by_key(|(d, _)| *d);
+    let allowed = if input.chars().count() <= 4 { 1 } else { 2 };
+    if prefixed.is_empty() {
+        match closest[..] {
+            [(best, value), (next, _), ..] if best <= allowed && next > best => {
+                return Match::Found(value)
+            }
+            [(best, value)] if best <= allowed => return Match::Found(value),
+            _ => {}
+        }
+    }
+
+    let mut suggestions = prefixed;
+    let close_enough = (allowed + 1).max(input.chars().count() / 2);
+    for (d, value) in closest {
+        if d <= close_enough && !suggestions.contains(&value) {
+            suggestions.push(value);
+        }
+    }
+    suggestions.truncate(MAX_SUGGESTIONS);
+    Match::NotFound(suggestions)
+}
+
+//edit distance where swapping two neighbouring letters counts as one typo (optimal string alignment)
+pub fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
@@ -7,25 +7,56 @@ use chrono::{DateTime, Utc};
 use peppi::metadata::Player as PlayerMD;
 use peppi::parse;
 use peppi::ParseError;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::character::Character;
+use crate::report::{GameDetailReport, PlayerDetail, StockLoss};
 use crate::stage::Stage;
 
 use std::convert::TryFrom;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+//frames the opponent has to go without being hit for the next hit to count as a new opening
+const OPENING_RESET_FRAMES: usize = 45;
+const FRAMES_PER_MINUTE: f64 = 3600.0;
+//slippi numbers a replay's frames from here, the `frame` column of frame exports
+pub const FIRST_FRAME: i32 = -123;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct GameData {
     pub player_char: Character,
     pub opponent_char: Character,
     pub stage: Stage,
     pub match_result: MatchResult,
     pub timestamp: DateTime<Utc>,
+    pub opponent_code: String,
+    //the percent each of the player's stocks was lost at, and the same for the opponent's
+    #[serde(default)]
+    pub death_percents: Vec<f32>,
+    #[serde(default)]
+    pub kill_percents: Vec<f32>,
+    //hits and damage for each move that connected, by the player and by the opponent
+    #[serde(default)]
+    pub moves: Vec<MoveUsage>,
+    #[serde(default)]
+    pub opponent_moves: Vec<MoveUsage>,
+    //the replay the game was read from, empty for games that didn't come from a file
+    #[serde(default)]
+    pub path: PathBuf,
+    //how long the game lasted, at 60 frames a second
+    #[serde(default)]
+    pub frames: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MoveUsage {
+    pub attack: u8,
+    pub hits: usize,
+    pub damage: f32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum MatchResult {
     Victory(MatchEndType),
     Loss(MatchEndType),
@@ -44,7 +75,7 @@ pub enum GameParseError {
     PeppiError(ParseError),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum MatchEndType {
     Stocks,
     Timeout,
@@ -52,18 +83,7 @@ pub enum MatchEndType {
 
 impl GameData {
     pub fn parse_game(game: Game, np_code: String) -> Result<Self, GameParseError> {
-        if game.metadata.players.as_ref().unwrap().len() > 2 {
-            return Err(GameParseError::IncorrectPlayerCount);
-        }
-        if !has_player(&game, &np_code)? {
-            return Err(GameParseError::GameDoesNotContainPlayer);
-        }
-        let player_num = match get_player_num(&game, np_code) {
-            Some(num) => num,
-            None => {
-                return Err(GameParseError::CorruptedPlayerData);
-            }
-        };
+        let player_num = get_player_num(&game, &np_code)?;
         let match_result = match get_match_result(&game, player_num) {
             Ok(game_res) => game_res,
             Err(e) => {
@@ -76,6 +96,8 @@ impl GameData {
 
         let timestamp = game.metadata.date.unwrap();
 
+        let opponent_code = get_np_code(get_players(&game)?, 1 - player_num)?.to_string();
+
         let stage_num = game.start.stage.0 as usize;
 
         if stage_num == 0 || stage_num == 1 || stage_num == 21 || stage_num > 32 {
@@ -84,18 +106,60 @@ impl GameData {
 
         let stage = Stage::try_from(stage_num).unwrap();
 
+        let death_percents = get_death_percents(&game, player_num);
+        let kill_percents = get_death_percents(&game, 1 - player_num);
+        let moves = get_moves(&game, player_num);
+        let opponent_moves = get_moves(&game, 1 - player_num);
+        let frames = match &game.frames {
+            Frames::P2(d) => d.len(),
+            _ => 0,
+        };
+
         Ok(Self {
             player_char,
             opponent_char,
             stage,
             match_result,
             timestamp,
+            opponent_code,
+            death_percents,
+            kill_percents,
+            moves,
+            opponent_moves,
+            path: PathBuf::new(),
+            frames,
+        })
+    }
+
+    //reads and parses a whole replay file for the player with the given netplay code
+    pub fn from_replay(path: &Path, np_code: &str) -> Result<Self, GameParseError> {
+        let mut game =
+            GameData::parse_game(GameData::get_game_data(path, false)?, np_code.to_string())?;
+        game.path = path.to_path_buf();
+        Ok(game)
+    }
+
+    //a breakdown of a single replay for `game`. the cache only keeps totals, so every frame is read again
+    pub fn detail(path: &Path, np_code: &str) -> Result<GameDetailReport, GameParseError> {
+        let replay = GameData::get_game_data(path, false)?;
+        let player = get_player_num(&replay, np_code)?;
+        let mut players = [
+            get_player_detail(&replay, player)?,
+            get_player_detail(&replay, 1 - player)?,
+        ];
+        let stocks = get_stock_losses(&replay, player, &mut players);
+        let mut game = GameData::parse_game(replay, np_code.to_string())?;
+        game.path = path.to_path_buf();
+        Ok(GameDetailReport {
+            game,
+            players,
+            stocks,
         })
     }
 
     pub fn get_game_data(path: &Path, skip_frames: bool) -> Result<Game, GameParseError> {
         match peppi::game(
-            &mut File::open(&path).unwrap(),
+            &mut File::open(path).unwrap(),
             Some(parse::Opts { skip_frames }),
         ) {
             Ok(val) => Ok(val),
@@ -103,32 +167,47 @@ impl GameData {
         }
     }
 
+    pub fn seconds(&self) -> usize {
+        self.frames / 60
+    }
+
+    pub fn length(&self) -> String {
+        clock(self.frames)
+    }
+
     pub fn is_victory(&self) -> bool {
         matches!(self.match_result, MatchResult::Victory(_))
     }
-}
 
-fn has_player(game: &Game, np_code: &str) -> Result<bool, GameParseError> {
-    let players = game.metadata.players.as_ref().unwrap();
-    let p1_np_code = get_np_code(&players, 0)?;
-    let p2_np_code = get_np_code(&players, 1)?;
+    //score used for rating calculations, games that were quit out of don't count
+    pub fn score(&self) -> Option<f64> {
+        match self.match_result {
+            MatchResult::Victory(_) => Some(1.0),
+            MatchResult::Loss(_) => Some(0.0),
+            MatchResult::Tie => Some(0.5),
+            MatchResult::EarlyEnd(_) => None,
+        }
+    }
+}
 
-    Ok(p1_np_code == np_code || p2_np_code == np_code)
+//replays from consoles or offline play have no netplay codes and can't be matched to the player
+fn get_players(game: &Game) -> Result<&[PlayerMD], GameParseError> {
+    match game.metadata.players.as_deref() {
+        Some(players) if players.len() == 2 => Ok(players),
+        Some(_) => Err(GameParseError::IncorrectPlayerCount),
+        None => Err(GameParseError::CorruptedPlayerData),
+    }
 }
 
-fn get_player_num(game: &Game, np_code: String) -> Option<usize> {
-    let players = game.metadata.players.as_ref().unwrap();
-    let p2_md = players.get(1).unwrap();
-    let p2_np_code = match &p2_md.netplay {
-        Some(c) => &c.code,
-        None => {
-            return None;
-        }
-    };
-    if p2_np_code == &np_code {
-        Some(1)
+//which of the two ports has the player's code
+fn get_player_num(game: &Game, np_code: &str) -> Result<usize, GameParseError> {
+    let players = get_players(game)?;
+    if get_np_code(players, 0)? == np_code {
+        Ok(0)
+    } else if get_np_code(players, 1)? == np_code {
+        Ok(1)
     } else {
-        Some(0)
+        Err(GameParseError::GameDoesNotContainPlayer)
     }
 }
 
@@ -149,8 +228,7 @@ fn get_match_result(game: &Game, player_num: usize) -> Result<MatchResult, GameP
 
     let ev20 = game.end.v2_0.as_ref().unwrap();
 
-    if ev20.lras_initiator != None {
-        let port = ev20.lras_initiator.unwrap();
+    if let Some(port) = ev20.lras_initiator {
         match port {
             Port::P1 => {
                 return Ok(MatchResult::EarlyEnd(1));
@@ -183,6 +261,181 @@ fn get_match_result(game: &Game, player_num: usize) -> Result<MatchResult, GameP
     Ok(result)
 }
 
+//a stock is lost on the frame the stock count drops, so the damage is taken from the frame before
+fn get_death_percents(game: &Game, player: usize) -> Vec<f32> {
+    let data = match &game.frames {
+        Frames::P2(d) => d,
+        _ => return Vec::new(),
+    };
+    data.windows(2)
+        .filter(|w| w[1].ports[player].leader.post.stocks < w[0].ports[player].leader.post.stocks)
+        .map(|w| w[0].ports[player].leader.post.damage)
+        .collect()
+}
+
+//like `3:07`
+pub fn clock(frames: usize) -> String {
+    let seconds = frames / 60;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+//damage taken only counts increases, since it drops back to 0 with each stock
+fn get_player_detail(game: &Game, player: usize) -> Result<PlayerDetail, GameParseError> {
+    let players = get_players(game)?;
+    let mut detail = PlayerDetail {
+        code: get_np_code(players, player)?.to_string(),
+        character: get_char(game, player)?,
+        damage_dealt: 0.0,
+        damage_taken: 0.0,
+        openings: 0,
+        kills: 0,
+        kill_moves: Vec::new(),
+        death_percents: get_death_percents(game, player),
+        l_cancels: 0,
+        missed_l_cancels: 0,
+        apm: 0.0,
+    };
+    let data = match &game.frames {
+        Frames::P2(d) => d,
+        _ => return Ok(detail),
+    };
+    let opponent = 1 - player;
+    let mut last_hit: Option<usize> = None;
+    let mut presses = 0;
+    for (i, w) in data.windows(2).enumerate() {
+        let (before, after) = (&w[0].ports[player].leader, &w[1].ports[player].leader);
+        let dealt =
+            w[1].ports[opponent].leader.post.damage - w[0].ports[opponent].leader.post.damage;
+        if dealt > 0.0 {
+            detail.damage_dealt += dealt;
+            if last_hit.is_none_or(|hit| i - hit >= OPENING_RESET_FRAMES) {
+                detail.openings += 1;
+            }
+            last_hit = Some(i);
+        }
+        let taken = after.post.damage - before.post.damage;
+        if taken > 0.0 {
+            detail.damage_taken += taken;
+        }
+        //the result stays set for a few frames after landing
+        if after.post.l_cancel != before.post.l_cancel {
+            match after.post.l_cancel {
+                Some(Some(true)) => detail.l_cancels += 1,
+                Some(Some(false)) => detail.missed_l_cancels += 1,
+                _ => (),
+            }
+        }
+        let pressed = after.pre.buttons.physical.0 & !before.pre.buttons.physical.0;
+        presses += pressed.count_ones();
+    }
+    if !data.is_empty() {
+        detail.apm = presses as f64 / (data.len() as f64 / FRAMES_PER_MINUTE);
+    }
+    Ok(detail)
+}
+
+//every stock either player lost in order, filling in the kills of whoever took it.
+//the kill move is the last one the opponent landed, unless the stock was lost without being hit
+fn get_stock_losses(game: &Game, player: usize, players: &mut [PlayerDetail; 2]) -> Vec<StockLoss> {
+    let data = match &game.frames {
+        Frames::P2(d) => d,
+        _ => return Vec::new(),
+    };
+    let mut stocks = Vec::new();
+    for (i, w) in data.windows(2).enumerate() {
+        for (index, &port) in [player, 1 - player].iter().enumerate() {
+            let (before, after) = (&w[0].ports[port].leader.post, &w[1].ports[port].leader.post);
+            if after.stocks >= before.stocks {
+                continue;
+            }
+            let killed_by = before.last_hit_by.and_then(|_| {
+                w[0].ports[1 - port]
+                    .leader
+                    .post
+                    .last_attack_landed
+                    .map(|attack| move_name(attack.0))
+            });
+            let killer = &mut players[1 - index];
+            killer.kills += 1;
+            killer.kill_moves.extend(killed_by);
+            stocks.push(StockLoss {
+                frame: i + 1,
+                lost_by: players[index].code.clone(),
+                stocks_left: after.stocks,
+                percent: before.damage,
+                killed_by,
+            });
+        }
+    }
+    stocks
+}
+
+//any damage the opponent takes is credited to the last move the player landed, which only holds up in singles
+fn get_moves(game: &Game, player: usize) -> Vec<MoveUsage> {
+    let data = match &game.frames {
+        Frames::P2(d) => d,
+        _ => return Vec::new(),
+    };
+    let opponent = 1 - player;
+    let mut moves: Vec<MoveUsage> = Vec::new();
+    for w in data.windows(2) {
+        let dealt =
+            w[1].ports[opponent].leader.post.damage - w[0].ports[opponent].leader.post.damage;
+        if dealt <= 0.0 {
+            continue;
+        }
+        let attack = match w[1].ports[player].leader.post.last_attack_landed {
+            Some(attack) => attack.0,
+            None => continue,
+        };
+        match moves.iter_mut().find(|m| m.attack == attack) {
+            Some(usage) => {
+                usage.hits += 1;
+                usage.damage += dealt;
+            }
+            None => moves.push(MoveUsage {
+                attack,
+                hits: 1,
+                damage: dealt,
+            }),
+        }
+    }
+    moves.sort_by_key(|m| m.attack);
+    moves
+}
+
+//names for the attack ids the game uses for staling, unlisted ids are things like items and stage hazards
+pub fn move_name(attack: u8) -> &'static str {
+    match attack {
+        2..=4 => "Jab",
+        5 => "Rapid jabs",
+        6 => "Dash attack",
+        7 => "Forward tilt",
+        8 => "Up tilt",
+        9 => "Down tilt",
+        10 => "Forward smash",
+        11 => "Up smash",
+        12 => "Down smash",
+        13 => "Neutral air",
+        14 => "Forward air",
+        15 => "Back air",
+        16 => "Up air",
+        17 => "Down air",
+        18 => "Neutral B",
+        19 => "Side B",
+        20 => "Up B",
+        21 => "Down B",
+        50 | 51 => "Getup attack",
+        52 => "Pummel",
+        53 => "Forward throw",
+        54 => "Back throw",
+        55 => "Up throw",
+        56 => "Down throw",
+        61 | 62 => "Edge attack",
+        _ => "Other",
+    }
+}
+
 fn get_char(game: &Game, player: usize) -> Result<Character, GameParseError> {
     let char_num = match game.start.players.get(player) {
         Some(character) => character,
@@ -200,7 +453,9 @@ fn get_char(game: &Game, player: usize) -> Result<Character, GameParseError> {
 }
 
 fn get_np_code(players: &[PlayerMD], p_number: usize) -> Result<&str, GameParseError> {
-    let p_md = players.get(p_number).unwrap();
+    let p_md = players
+        .get(p_number)
+        .ok_or(GameParseError::IncorrectPlayerCount)?;
     match &p_md.netplay {
         Some(c) => Ok(&c.code),
         None => Err(GameParseError::CorruptedPlayerData),
@@ -238,3 +493,22 @@ impl fmt::Display for MatchEndType {
         }
     }
 }
+
+impl fmt::Display for GameParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameParseError::CorruptedCharData(num) => write!(f, "unknown character id {}", num),
+            GameParseError::CorruptedStageData(num) => write!(f, "unknown stage id {}", num),
+            GameParseError::CorruptedPlayerData => {
+                write!(
+                    f,
+                    "the replay has no netplay codes, like replays from a console or offline play"
+                )
+            }
+            GameParseError::EmptyCharData => write!(f, "the replay has no character data"),
+            GameParseError::IncorrectPlayerCount => write!(f, "the replay isn't a singles game"),
+            GameParseError::GameDoesNotContainPlayer => write!(f, "your code isn't in the replay"),
+            GameParseError::PeppiError(e) => write!(f, "the replay couldn't be read ({:?})", e),
+        }
+    }
+}
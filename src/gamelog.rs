@@ -0,0 +1,93 @@
+use std::cmp::Reverse;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::gamedata::GameData;
+use crate::report::LoggedGame;
+
+//games shown per page of the log
+pub const PAGE_SIZE: usize = 20;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GameSort {
+    #[default]
+    Newest,
+    Oldest,
+    Longest,
+    Shortest,
+}
+
+//which page of the game log to show, and how. conditions like the opponent's character are
+//applied with a `Filter` beforehand, this only covers what the log adds on top
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GameLog {
+    pub sort: GameSort,
+    //starts at 1
+    pub page: usize,
+    //matched case insensitively against the opponent's code, both characters, the stage and the replay path
+    pub search: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct GameSortParseError(String);
+
+impl GameLog {
+    pub fn new() -> Self {
+        Self {
+            page: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn matches(&self, game: &GameData) -> bool {
+        let search = match &self.search {
+            Some(search) => search.to_lowercase(),
+            None => return true,
+        };
+        [
+            game.opponent_code.clone(),
+            format!("{}", game.player_char),
+            format!("{}", game.opponent_char),
+            format!("{}", game.stage),
+            game.path.to_string_lossy().into_owned(),
+        ]
+        .iter()
+        .any(|field| field.to_lowercase().contains(&search))
+    }
+}
+
+impl GameSort {
+    //games that tie keep their order, so games of the same length stay newest first
+    pub fn sort(self, games: &mut [LoggedGame]) {
+        match self {
+            GameSort::Newest => games.sort_by_key(|g| g.id),
+            GameSort::Oldest => games.sort_by_key(|g| Reverse(g.id)),
+            GameSort::Longest => games.sort_by_key(|g| Reverse(g.game.frames)),
+            GameSort::Shortest => games.sort_by_key(|g| g.game.frames),
+        }
+    }
+}
+
+//`date` and `length` put the newest and longest games first
+impl FromStr for GameSort {
+    type Err = GameSortParseError;
+    fn from_str(arg: &str) -> Result<Self, Self::Err> {
+        match arg {
+            "newest" | "date" => Ok(GameSort::Newest),
+            "oldest" => Ok(GameSort::Oldest),
+            "longest" | "length" => Ok(GameSort::Longest),
+            "shortest" => Ok(GameSort::Shortest),
+            _ => Err(GameSortParseError(arg.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for GameSortParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not a sort order. Use newest, oldest, longest or shortest.",
+            self.0
+        )
+    }
+}
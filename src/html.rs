@@ -0,0 +1,149 @@
+use chrono::Utc;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::chart;
+use crate::output::{Report, Table};
+use crate::player::Player;
+use crate::playerdata::PlayerData;
+use crate::report::GridReport;
+
+const RECENT_GAMES: usize = 25;
+
+//everything is inlined so the file can be opened anywhere without a network connection
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 1100px; color: #222; background: #fafafa; }
+h1 { margin-bottom: 0; }
+h2 { border-bottom: 2px solid #2a7; padding-bottom: 0.2em; margin-top: 2em; }
+.subtitle { color: #666; margin-top: 0.3em; }
+.scroll { overflow-x: auto; }
+table { border-collapse: collapse; margin: 1em 0; font-size: 0.9em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; white-space: nowrap; }
+th { background: #eee; }
+tr:nth-child(even) td { background: #f3f3f3; }
+td.winrate { background: var(--winrate) !important; }
+svg { border: 1px solid #ccc; max-width: 100%; height: auto; }
+";
+
+pub fn export(data: &PlayerData, path: &Path) -> io::Result<()> {
+    fs::write(path, render(data))
+}
+
+pub fn render(data: &PlayerData) -> String {
+    let overall = data.winrate(Player::Player);
+    let mut body = String::new();
+    body.push_str("<h1>Slippi stats</h1>\n");
+    body.push_str(&format!(
+        "<p class=\"subtitle\">{} games, generated {}</p>\n",
+        overall.record.games,
+        Utc::now().format("%Y-%m-%d %H:%M UTC")
+    ));
+    section(&mut body, "Overview", &table(&data.overview().to_table()));
+    section(&mut body, "Overall", &table(&overall.to_table()));
+    section(
+        &mut body,
+        "Weekly winrate",
+        &chart::trend(&data.trend(Player::Player)),
+    );
+    section(
+        &mut body,
+        "Characters",
+        &table(&data.characters(Player::Player).to_table()),
+    );
+    section(
+        &mut body,
+        "Stages",
+        &table(&data.stages(Player::Player).to_table()),
+    );
+    section(
+        &mut body,
+        "Matchups",
+        &table(&data.matchups(Player::Player).to_table()),
+    );
+    section(&mut body, "Matchup chart", &grid(&data.matchup_chart()));
+    section(&mut body, "Stage chart", &grid(&data.stage_chart()));
+    let deaths = data.deaths(Player::Player);
+    section(
+        &mut body,
+        "Percent at stock loss",
+        &format!(
+            "{}\n{}",
+            chart::histogram(&deaths),
+            table(&deaths.to_table())
+        ),
+    );
+    section(&mut body, "Sessions", &table(&data.sessions().to_table()));
+    section(
+        &mut body,
+        "Recent games",
+        &table(&data.recent(RECENT_GAMES).to_table()),
+    );
+
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Slippi stats</title>\n<style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        STYLE, body
+    )
+}
+
+fn section(body: &mut String, title: &str, content: &str) {
+    body.push_str(&format!(
+        "<section>\n<h2>{}</h2>\n<div class=\"scroll\">\n{}\n</div>\n</section>\n",
+        escape(title),
+        content
+    ));
+}
+
+pub fn table(table: &Table) -> String {
+    let mut html = String::from("<table>\n<tr>");
+    for header in &table.headers {
+        html.push_str(&format!("<th>{}</th>", escape(header)));
+    }
+    html.push_str("</tr>\n");
+    for row in &table.rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{}</td>", escape(cell)));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>");
+    html
+}
+
+fn grid<R: Display, C: Display>(grid: &GridReport<R, C>) -> String {
+    if grid.rows.is_empty() {
+        return String::from("<p>No data.</p>");
+    }
+    let mut html = String::from("<table>\n<tr><th></th>");
+    for column in &grid.columns {
+        html.push_str(&format!("<th>{}</th>", escape(&format!("{}", column))));
+    }
+    html.push_str("</tr>\n");
+    for (row, cells) in grid.rows.iter().zip(&grid.cells) {
+        html.push_str(&format!("<tr><th>{}</th>", escape(&format!("{}", row))));
+        for cell in cells {
+            if cell.games == 0 {
+                html.push_str("<td></td>");
+                continue;
+            }
+            html.push_str(&format!(
+                "<td class=\"winrate\" style=\"--winrate: {}\">{}</td>",
+                chart::winrate_color(cell.winrate()),
+                escape(&GridReport::<R, C>::cell_text(cell))
+            ));
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</table>");
+    html
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
@@ -1,137 +1,481 @@
+use crate::input_loop;
 use crate::text;
-use crate::{command_loop, input_loop};
 use std::fmt;
 use std::fs;
-use std::io;
 use std::path::PathBuf;
 use std::str::FromStr;
 
 use crate::playerdata::PlayerData;
+use crate::progress::Bar;
 
 use crate::character::Character;
-use crate::player::Player;
+use crate::cli;
+use crate::command::{self, Command, CommandError, Query};
+use crate::config::{Config, ConfigCommand, ProfileCommand};
+use crate::gamelog::PAGE_SIZE;
+use crate::output::{self, OutputFormat};
+use crate::parsable_enum::GameDataCondition;
+use crate::script;
+use crate::shell::{self, Completion, Line};
 use crate::stage::Stage;
-use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug)]
 struct NetplayCode {
     name: String,
     num: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-struct CacheLocation {
-    np_code: NetplayCode,
-    path: PathBuf,
-}
-
 enum NetplayCodeParseError {
     InvalidCode,
 }
 
-pub fn load_data() -> PlayerData {
-    let cl = match fs::read_to_string("data.cache") {
-        Ok(c) => {
-            println!("Cache found, loading...");
-            match serde_json::from_str(&c) {
-                Ok(d) => d,
-                Err(_) => input_data(),
+//every command of the main loop with its help, and the help for each of its stats or sub commands
+type CommandHelp = (
+    &'static str,
+    &'static str,
+    &'static [(&'static str, &'static str)],
+);
+const COMMANDS: &[CommandHelp] = &[
+    (
+        "player",
+        text::PLAYER_HELP_TEXT,
+        &[
+            ("winrate", text::P_WINRATE_HELP_TEXT),
+            ("characters", text::P_CHARACTERS_HELP_TEXT),
+            ("stages", text::P_STAGES_HELP_TEXT),
+            ("matchups", text::P_MATCHUPS_HELP_TEXT),
+            ("overview", text::P_OVERVIEW_HELP_TEXT),
+            ("deaths", text::P_DEATHS_HELP_TEXT),
+        ],
+    ),
+    (
+        "character",
+        text::CHARACTER_HELP_TEXT,
+        &[
+            ("winrate", text::C_WINRATE_HELP_TEXT),
+            ("stages", text::C_STAGES_HELP_TEXT),
+            ("matchups", text::C_MATCHUPS_HELP_TEXT),
+            ("deaths", text::C_DEATHS_HELP_TEXT),
+        ],
+    ),
+    (
+        "stage",
+        text::STAGE_HELP_TEXT,
+        &[
+            ("winrate", text::S_WINRATE_HELP_TEXT),
+            ("characters", text::S_CHARACTERS_HELP_TEXT),
+            ("matchups", text::S_MATCHUPS_HELP_TEXT),
+            ("deaths", text::S_DEATHS_HELP_TEXT),
+        ],
+    ),
+    ("matchup", text::MATCHUP_HELP_TEXT, &[]),
+    (
+        "chart",
+        text::CHART_HELP_TEXT,
+        &[
+            ("matchups", text::CH_MATCHUPS_HELP_TEXT),
+            ("stages", text::CH_STAGES_HELP_TEXT),
+        ],
+    ),
+    ("compare", text::COMPARE_HELP_TEXT, &[]),
+    ("last", text::LAST_HELP_TEXT, &[]),
+    ("games", text::GAMES_HELP_TEXT, &[]),
+    ("game", text::GAME_HELP_TEXT, &[]),
+    (
+        "rating",
+        text::RATING_HELP_TEXT,
+        &[
+            ("current", text::RA_CURRENT_HELP_TEXT),
+            ("history", text::RA_HISTORY_HELP_TEXT),
+            ("character", text::RA_CHARACTER_HELP_TEXT),
+        ],
+    ),
+    (
+        "ranking",
+        text::RANKING_HELP_TEXT,
+        &[
+            ("lower bound", text::R_LOWER_BOUND_HELP_TEXT),
+            ("shrunk", text::R_SHRUNK_HELP_TEXT),
+        ],
+    ),
+    ("sessions", text::SESSIONS_HELP_TEXT, &[]),
+    ("trend", text::TREND_HELP_TEXT, &[]),
+    (
+        "export",
+        text::EXPORT_HELP_TEXT,
+        &[
+            ("html", text::EX_HTML_HELP_TEXT),
+            ("svg", text::EX_SVG_HELP_TEXT),
+            ("sqlite", text::EX_SQLITE_HELP_TEXT),
+            ("frames", text::EX_FRAMES_HELP_TEXT),
+            ("dolphin", text::EX_DOLPHIN_HELP_TEXT),
+        ],
+    ),
+    ("sql", text::SQL_HELP_TEXT, &[]),
+    ("run", text::RUN_HELP_TEXT, &[]),
+    ("format", text::FORMAT_HELP_TEXT, &[]),
+    (
+        "config",
+        text::CONFIG_HELP_TEXT,
+        &[
+            ("show", text::CO_SHOW_HELP_TEXT),
+            ("set", text::CO_SET_HELP_TEXT),
+            ("unset", text::CO_UNSET_HELP_TEXT),
+            ("alias", text::CO_ALIAS_HELP_TEXT),
+            ("unalias", text::CO_UNALIAS_HELP_TEXT),
+            ("query", text::CO_QUERY_HELP_TEXT),
+            ("unquery", text::CO_UNQUERY_HELP_TEXT),
+        ],
+    ),
+    (
+        "profile",
+        text::PROFILE_HELP_TEXT,
+        &[
+            ("list", text::PR_LIST_HELP_TEXT),
+            ("add", text::PR_ADD_HELP_TEXT),
+            ("remove", text::PR_REMOVE_HELP_TEXT),
+            ("switch", text::PR_SWITCH_HELP_TEXT),
+            ("compare", text::PR_COMPARE_HELP_TEXT),
+        ],
+    ),
+    ("change cache", text::CHANGECACHE_HELP_TEXT, &[]),
+    ("end", text::END_HELP_TEXT, &[]),
+];
+
+//loads the replays of the active profile, asking for the code and directory if they aren't set
+pub fn load_data(config: &mut Config) -> PlayerData {
+    if let Err(e) = config.active().register_aliases() {
+        println!("{}", e);
+    }
+    loop {
+        let profile = config.active();
+        let (code, dir) = match (profile.code.clone(), profile.dir.clone()) {
+            (Some(code), Some(dir)) => {
+                println!("Loading replays for {} from {:?}...", code, dir);
+                (code, dir)
+            }
+            _ => input_data(config),
+        };
+        let cache_dir = profile.cache_dir(&dir);
+        match PlayerData::parse_dir_with(dir.clone(), code, &cache_dir, &mut Bar::new()) {
+            Ok(mut data) => {
+                data.set_ranking(profile.ranking());
+                let mut codes: Vec<String> = data
+                    .games()
+                    .iter()
+                    .map(|g| g.opponent_code.clone())
+                    .collect();
+                codes.sort();
+                codes.dedup();
+                shell::set_codes(codes);
+                return data;
+            }
+            Err(e) => {
+                println!("Couldn't load replays from {:?} due to error `{}`", dir, e);
+                config.active_mut().dir = None;
             }
         }
-        Err(_) => input_data(),
-    };
-    PlayerData::parse_dir(cl.path, format!("{}", cl.np_code))
+    }
+}
+
+pub fn parse_code(code: &str) -> Option<String> {
+    NetplayCode::from_str(code)
+        .ok()
+        .map(|np_code| format!("{}", np_code))
 }
 
-fn input_data() -> CacheLocation {
+fn input_data(config: &mut Config) -> (String, PathBuf) {
     println!("Please input your np code:");
-    let np_code = input_loop!(NetplayCode);
+    let np_code = format!("{}", input_loop!(NetplayCode));
     println!("Enter the directory where your replays are stored:");
-    let path = input_loop!(PathBuf);
-    let cl = CacheLocation { np_code, path };
-    let serial = serde_json::to_string(&cl).unwrap();
-    match fs::write("data.cache", serial) {
+    let path = input_loop!(PathBuf, Completion::Path);
+    let profile = config.active_mut();
+    profile.code = Some(np_code.clone());
+    profile.dir = Some(path.clone());
+    match config.save() {
         Ok(_) => println!("Data saved."),
-        Err(e) => println!("Cache location could not be saved do to error `{:?}`", e),
+        Err(e) => println!("{}", e),
     };
-    cl
-}
-
-pub fn main_loop(results: PlayerData) -> bool {
-    command_loop!(
-        false,
-        "player", text::PLAYER_HELP_TEXT => player(&results),
-        "character", text::CHARACTER_HELP_TEXT => character(&results),
-        "stage", text::STAGE_HELP_TEXT => stage(&results),
-        "matchup", text::MATCHUP_HELP_TEXT => matchup(&results),
-        "last", text::LAST_HELP_TEXT => last(&results),
-        "change cache", text::CHANGECACHE_HELP_TEXT => {
-            change_cache();
-            return false;
-        },
-        "end", text::END_HELP_TEXT => {
-            break;
+    (np_code, path)
+}
+
+pub fn main_loop(mut results: PlayerData, format: &mut OutputFormat, config: &mut Config) -> bool {
+    let names: Vec<&'static str> = COMMANDS.iter().map(|(cmd, _, _)| *cmd).collect();
+    let help_txt = format_help_txt(names.iter().map(|cmd| format!("{:?}, ", cmd)).collect());
+    println!("{}", help_txt);
+    loop {
+        let completion = Completion::Query([&names[..], &["help"]].concat(), queries(config));
+        let line = match shell::read_line(completion) {
+            Line::Text(line) => line,
+            Line::Cancel => {
+                println!("Type `end` or press Ctrl-D to exit.");
+                continue;
+            }
+            Line::Exit => break,
+        };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let words: Vec<String> = tokens.iter().map(|t| t.to_lowercase()).collect();
+        let words: Vec<&str> = words.iter().map(|w| w.as_str()).collect();
+        match &words[..] {
+            [] => {}
+            ["help"] => {
+                println!("{}", help_txt);
+                if !config.queries.is_empty() {
+                    println!("Saved queries: {}", queries(config).join(", "));
+                }
+                println!("{}", text::QUERY_HELP_TEXT);
+            }
+            ["help", name] if config.queries.contains_key(*name) => {
+                println!("{} = {}", name, config.queries[*name]);
+            }
+            ["help", topic @ ..] => help(topic),
+            ["format"] => {
+                println!("Input an output format (text, table, json, csv or markdown).");
+                *format = input_loop!(OutputFormat);
+            }
+            ["format", name] => match name.parse() {
+                Ok(new_format) => *format = new_format,
+                Err(e) => println!("{}", e),
+            },
+            ["config", ..] => edit_config(config, &tokens),
+            ["profile", ..] => {
+                if profiles(config, &tokens, *format) {
+                    return false;
+                }
+            }
+            ["run"] => println!("Missing script file."),
+            ["run", ..] => run_script(&mut results, tokens[1..].join(" "), *format, config),
+            ["change", "cache"] => {
+                change_cache(config);
+                return false;
+            }
+            ["end"] => break,
+            _ => match config.expand_query(&tokens) {
+                Some(saved) => {
+                    let saved: Vec<&str> = saved.iter().map(|t| t.as_str()).collect();
+                    query(&mut results, &saved, *format);
+                }
+                None => query(&mut results, &tokens, *format),
+            },
         }
-    );
+    }
     true
 }
 
-fn change_cache() {
-    match fs::remove_file("data.cache") {
-        Ok(_) => println!("Prevous cache location removed."),
-        Err(e) => {
-            println!(
-                "Couldn't delete previous cache location data due to error `{:?}`",
-                e
-            );
+fn run_script(data: &mut PlayerData, path: String, format: OutputFormat, config: &Config) {
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => return println!("Couldn't read script {:?}: {}", path, e),
+    };
+    let result = match script::parse(&text, config) {
+        Ok(steps) => script::run(&steps, data, format),
+        Err(e) => return println!("{}, {}", path, e),
+    };
+    if let Err(e) = result {
+        println!("Command failed: {}", e);
+    }
+}
+
+fn queries(config: &Config) -> Vec<String> {
+    config.queries.keys().cloned().collect()
+}
+
+//runs a command typed on one line, asking for any arguments it's missing
+fn query(data: &mut PlayerData, tokens: &[&str], format: OutputFormat) {
+    if let Some(query) = complete(tokens, command::parse) {
+        run_query(data, query, format);
+    }
+}
+
+//asks for the arguments `parse` says are missing until the line parses. None if it can't be
+//parsed or the user backed out
+fn complete<T>(tokens: &[&str], parse: impl Fn(&[&str]) -> Result<T, CommandError>) -> Option<T> {
+    let mut tokens: Vec<String> = tokens.iter().map(|t| t.to_string()).collect();
+    loop {
+        let words: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+        let kind = match parse(&words) {
+            Ok(parsed) => return Some(parsed),
+            Err(CommandError::MissingArgument(kind)) => kind,
+            Err(e) => {
+                println!("{}", e);
+                return None;
+            }
+        };
+        let choices = command::choices(&tokens[0].to_lowercase(), kind);
+        let completion = if !choices.is_empty() {
+            println!("Input the {} ({}):", kind, choices.join(", "));
+            Completion::Commands(choices)
+        } else {
+            println!("Input the {}:", kind);
+            match kind {
+                "character" | "player character" | "opponent character" => {
+                    Completion::Names(shell::names::<Character>())
+                }
+                "stage" => Completion::Names(shell::names::<Stage>()),
+                "file path" | "game number or replay path" | "replay directory" => Completion::Path,
+                "command to save" => Completion::Conditions,
+                "query" => {
+                    println!("{}", text::SQL_TABLES_TEXT);
+                    Completion::Nothing
+                }
+                "second set of conditions" => Completion::Conditions,
+                _ if kind.ends_with("conditions") => {
+                    println!("{}", text::FILTER_SYNTAX_TEXT);
+                    Completion::Conditions
+                }
+                _ => Completion::Nothing,
+            }
+        };
+        let answer = read_argument(completion)?;
+        //the two sets of conditions of `compare` are split by a slash
+        if kind == "second set of conditions" {
+            tokens.push(String::from("/"));
+        }
+        tokens.extend(answer.split_whitespace().map(String::from));
+    }
+}
+
+//the game log waits for enter before showing each page after the first
+fn run_query(data: &mut PlayerData, mut query: Query, format: OutputFormat) {
+    loop {
+        if let Err(e) = command::run(&query, data, format) {
+            println!("Command failed: {}", e);
+            return;
+        }
+        let filter = &query.filter;
+        let log = match &mut query.command {
+            Command::Games(log) => log,
+            _ => return,
+        };
+        let total = data
+            .games()
+            .iter()
+            .filter(|g| filter.game_data_condition(g) && log.matches(g))
+            .count();
+        if log.page.max(1) * PAGE_SIZE >= total {
+            return;
+        }
+        println!("Press Enter for the next page, or type anything else to stop.");
+        match shell::read_line(Completion::Nothing) {
+            Line::Text(input) if input.trim().is_empty() => log.page = log.page.max(1) + 1,
+            Line::Exit => shell::exit(),
+            _ => return,
         }
     }
 }
 
-fn player(data: &PlayerData) {
-    command_loop!(
-        true,
-        "winrate", text::P_WINRATE_HELP_TEXT => data.winrate(Player::Player),
-        "characters", text::P_CHARACTERS_HELP_TEXT => data.characters(Player::Player),
-        "stages", text::P_STAGES_HELP_TEXT => data.stages(Player::Player),
-        "matchups", text::P_MATCHUPS_HELP_TEXT => data.matchups(Player::Player),
-        "overview", text::P_OVERVIEW_HELP_TEXT => data.overview()
-    );
+//prints the help for a command, or for one of its stats or sub commands
+fn help(topic: &[&str]) {
+    for (cmd, cmd_help, subs) in COMMANDS {
+        let words: Vec<&str> = cmd.split(' ').collect();
+        if !topic.starts_with(&words) {
+            continue;
+        }
+        let rest = &topic[words.len()..];
+        match rest {
+            [] => {
+                println!("{}", cmd_help);
+                for (sub, sub_help) in *subs {
+                    println!("  {} - {}", sub, sub_help);
+                }
+            }
+            _ => match subs.iter().find(|(sub, _)| *sub == rest.join(" ")) {
+                Some((_, sub_help)) => println!("{}", sub_help),
+                None => println!("Unrecognized command"),
+            },
+        }
+        return;
+    }
+    println!("Unrecognized command");
 }
 
-fn character(data: &PlayerData) {
-    println!("Input the name of a character.");
-    let character = input_loop!(Character);
-    command_loop!(
-        true,
-        "winrate", text::C_WINRATE_HELP_TEXT => data.winrate(character),
-        "stages", text::C_STAGES_HELP_TEXT => data.stages(character),
-        "matchups", text::C_MATCHUPS_HELP_TEXT => data.matchups(character)
-    );
+fn change_cache(config: &mut Config) {
+    let profile = config.active_mut();
+    profile.code = None;
+    profile.dir = None;
+    match config.save() {
+        Ok(_) => println!("Previous replay location removed."),
+        Err(e) => println!("{}", e),
+    }
 }
 
-fn stage(data: &PlayerData) {
-    let stage = input_loop!(Stage);
-    command_loop!(
-        true,
-        "winrate", text::S_WINRATE_HELP_TEXT => data.winrate(stage),
-        "characters", text::S_CHARACTERS_HELP_TEXT => data.characters(stage),
-        "matchups", text::S_MATCHUPS_HELP_TEXT => data.matchups(stage)
-    );
+//runs a `profile` command typed on one line, asking for any arguments it's missing. returns true
+//if a different profile has to be loaded
+pub fn profiles(config: &mut Config, tokens: &[&str], format: OutputFormat) -> bool {
+    let command = match complete(tokens, |tokens| ProfileCommand::parse(&tokens[1..])) {
+        Some(command) => command,
+        None => return false,
+    };
+    match command {
+        ProfileCommand::List => cli::print_profiles(config),
+        ProfileCommand::Compare(first, second, filter) => {
+            match cli::compare_profiles(config, &first, &second, &filter) {
+                Ok(report) => output::print(&report, format),
+                Err(e) => println!("{}", e),
+            }
+        }
+        command => {
+            let was_active =
+                matches!(&command, ProfileCommand::Remove(name) if name == config.active_name());
+            if let Err(e) = config.run_profile(&command) {
+                println!("{}", e);
+                return false;
+            }
+            match command {
+                ProfileCommand::Add(name, ..) => {
+                    println!(
+                        "Profile `{}` added, use `profile switch {}` to load it.",
+                        name, name
+                    )
+                }
+                ProfileCommand::Remove(name) => {
+                    println!("Profile `{}` removed.", name);
+                    return was_active;
+                }
+                _ => return true,
+            }
+        }
+    }
+    false
 }
 
-fn matchup(data: &PlayerData) {
-    println!("Input player character:");
-    let player_char = input_loop!(Character);
-    println!("Input opponent character:");
-    let opponent_char = input_loop!(Character);
-    data.matchup(player_char, opponent_char);
+//runs a `config` command typed on one line, asking for any arguments it's missing
+fn edit_config(config: &mut Config, tokens: &[&str]) {
+    let command = match complete(tokens, |tokens| ConfigCommand::parse(&tokens[1..])) {
+        Some(command) => command,
+        None => return,
+    };
+    match command {
+        ConfigCommand::Show => {
+            println!("{:?}", config.path());
+            match config.show() {
+                Ok(text) => print!("{}", text),
+                Err(e) => println!("{}", e),
+            }
+        }
+        ConfigCommand::Path => println!("{:?}", config.path()),
+        command => match config.run(&command) {
+            Ok(()) => match command {
+                ConfigCommand::Set(..) => {
+                    println!("Config saved, changes apply the next time replays are loaded.")
+                }
+                ConfigCommand::Query(name, _) => {
+                    println!("Config saved, run it with `{}`.", name.to_lowercase())
+                }
+                _ => println!("Config saved."),
+            },
+            Err(e) => println!("{}", e),
+        },
+    }
 }
 
-fn last(data: &PlayerData) {
-    println!("Last how many games?");
-    let num = input_loop!(usize);
-    data.last(num);
+//None if the user backed out with ctrl-c or an empty line
+fn read_argument(completion: Completion) -> Option<String> {
+    match shell::read_line(completion) {
+        Line::Text(input) if !input.trim().is_empty() => Some(input.trim().to_string()),
+        Line::Text(_) | Line::Cancel => None,
+        Line::Exit => shell::exit(),
+    }
 }
 
 fn format_input(arg: String) -> String {
@@ -176,52 +520,20 @@ impl FromStr for NetplayCode {
     }
 }
 
-#[macro_export]
-macro_rules! command_loop {
-    ($break_at_end:expr, $ ($cmd:expr, $cmd_help_text:expr => $result:expr),*) => {
-        let mut cmds = Vec::new();
-        $ (cmds.push(format!("{}, ", stringify!($cmd)));) *
-        let help_txt = format_help_txt(cmds);
-        println!("{}", help_txt);
-        loop {
-            let mut input = String::new();
-            io::stdin()
-                .read_line(&mut input)
-                .expect("failed to read line");
-            let input = format_input(input);
-            match &input[..]{
-	            $($cmd => $result,)*
-	            "help" => {
-		            println!("{}", help_txt);
-		            println!("Type `help` followed by another command to get more info on that command.");
-		            continue;
-		            },
-	            $(x if x == &format!("help {}", $cmd) => {
-		            println!("{}", $cmd_help_text);
-		            continue;
-		            },)*
-	            _ => {
-		            println!("Unrecognized command");
-		            continue;
-		        }
-            }
-            if $break_at_end{//this is so we can keep the main input loop running, while ending the others after a subcommand is ran
-                break;
-            }
-            println!("{}", help_txt);//this is so the main input loop will have its commands reprinted after a loop, so the user is aware they've returned back to it
-        }
-    };
-}
-
 #[macro_export]
 macro_rules! input_loop {
-    ($output:ty) => {{
+    ($output:ty) => {
+        $crate::input_loop!($output, $crate::shell::Completion::Nothing)
+    };
+    ($output:ty, $completion:expr) => {{
         let arg: $output;
         loop {
-            let mut input = String::new();
-            io::stdin()
-                .read_line(&mut input)
-                .expect("failed to read line");
+            let input = match $crate::shell::read_line($completion) {
+                $crate::shell::Line::Text(input) => input,
+                //there's nothing to go back to, so ask again
+                $crate::shell::Line::Cancel => continue,
+                $crate::shell::Line::Exit => $crate::shell::exit(),
+            };
             let input = &format_input(input);
             arg = match input.parse() {
                 Ok(fs) => fs,
@@ -1,18 +1,140 @@
-mod character;
-mod gamedata;
+//the core library: reading replays and aggregating the stats from them
+pub mod character;
+pub mod filter;
+pub mod fuzzy;
+pub mod gamedata;
+pub mod gamelog;
+pub mod parsable_enum;
+pub mod player;
+pub mod playerdata;
+pub mod progress;
+pub mod rating;
+pub mod report;
+pub mod stage;
+pub mod stats;
+
+mod cache;
+
+//the command line program built on top of it
+#[cfg(feature = "cli")]
+mod chart;
+#[cfg(feature = "cli")]
+pub mod cli;
+#[cfg(feature = "cli")]
+mod command;
+#[cfg(feature = "cli")]
+mod config;
+#[cfg(feature = "cli")]
+mod database;
+#[cfg(feature = "cli")]
+mod dolphin;
+#[cfg(feature = "cli")]
+mod frames;
+#[cfg(feature = "cli")]
+mod html;
+#[cfg(feature = "cli")]
 pub mod input;
-mod parsable_enum;
-mod player;
-mod playerdata;
-mod stage;
+#[cfg(feature = "cli")]
+mod output;
+#[cfg(feature = "cli")]
+mod script;
+#[cfg(feature = "cli")]
+mod server;
+#[cfg(feature = "cli")]
+mod shell;
+#[cfg(feature = "cli")]
 mod text;
+#[cfg(feature = "cli")]
+mod tui;
 
 #[cfg(test)]
 mod tests {
     use crate::character::Character;
+    #[cfg(feature = "cli")]
+    use crate::command::{self, Command, Export, GameRef, Stat};
+    #[cfg(feature = "cli")]
+    use crate::config::{Config, ConfigCommand, Profile};
+    use crate::filter::{self, Filter};
+    #[cfg(feature = "cli")]
+    use crate::gamedata::FIRST_FRAME;
+    use crate::gamedata::{GameData, MatchEndType, MatchResult, MoveUsage};
+    use crate::gamelog::{GameLog, GameSort};
+    #[cfg(feature = "cli")]
+    use crate::output::{self, OutputFormat};
+    use crate::parsable_enum;
+    use crate::player::Player;
+    use crate::playerdata::PlayerData;
+    use crate::progress::{Progress, ScanSummary};
+    #[cfg(feature = "cli")]
+    use crate::report::StockLoss;
     use crate::stage::Stage;
+    use crate::stats;
+    #[cfg(feature = "cli")]
+    use crate::{chart, database, dolphin, frames, html, input, script, server};
+    use chrono::TimeZone;
     use std::convert::TryFrom;
     use std::str::FromStr;
+
+    fn test_game(
+        player_char: Character,
+        opponent_char: Character,
+        stage: Stage,
+        win: bool,
+    ) -> GameData {
+        let match_result = if win {
+            MatchResult::Victory(MatchEndType::Stocks)
+        } else {
+            MatchResult::Loss(MatchEndType::Stocks)
+        };
+        GameData {
+            player_char,
+            opponent_char,
+            stage,
+            match_result,
+            timestamp: chrono::Utc.with_ymd_and_hms(2021, 3, 1, 12, 0, 0).unwrap(),
+            opponent_code: String::from("TEST#1"),
+            death_percents: vec![80.0, 120.0],
+            kill_percents: vec![95.0, 130.0, 210.0],
+            moves: vec![MoveUsage {
+                attack: 14,
+                hits: 3,
+                damage: 40.0,
+            }],
+            opponent_moves: Vec::new(),
+            path: std::path::PathBuf::from("Game_20210301T120000.slp"),
+            frames: 60 * 150,
+        }
+    }
+
+    fn test_data() -> PlayerData {
+        let mut data = PlayerData::new();
+        data.add_game(test_game(
+            Character::Fox,
+            Character::Marth,
+            Stage::Battlefield,
+            true,
+        ));
+        data.add_game(test_game(
+            Character::Fox,
+            Character::Marth,
+            Stage::FinalDestination,
+            false,
+        ));
+        data.add_game(test_game(
+            Character::Fox,
+            Character::Sheik,
+            Stage::Battlefield,
+            true,
+        ));
+        data.add_game(test_game(
+            Character::Falco,
+            Character::Marth,
+            Stage::Battlefield,
+            false,
+        ));
+        data
+    }
+
     #[test]
     fn char_parse_from_usize() {
         let char_from_usize = Character::try_from(0).unwrap();
@@ -24,6 +146,31 @@ mod tests {
         assert_eq!(Character::CaptainFalcon, char_from_str);
     }
     #[test]
+    fn fuzzy_parse_typos_prefixes_and_aliases() {
+        assert_eq!(Character::Fox, Character::from_str("foxx").unwrap());
+        assert_eq!(Character::Marth, Character::from_str("Marht").unwrap());
+        assert_eq!(Character::Pikachu, Character::from_str("pika").unwrap());
+        assert_eq!(Stage::FinalDestination, Stage::from_str("final").unwrap());
+        let message = format!("{}", Character::from_str("fal").unwrap_err());
+        assert!(message.contains("Did you mean"));
+        assert!(message.contains("Falco") && message.contains("Captain Falcon"));
+        //the alias table is shared by every test, so the alias is taken out again even on failure
+        struct AliasGuard;
+        impl Drop for AliasGuard {
+            fn drop(&mut self) {
+                parsable_enum::remove_alias::<Character>("zzcf");
+            }
+        }
+        let guard = AliasGuard;
+        parsable_enum::add_alias("zzcf", Character::CaptainFalcon);
+        assert_eq!(
+            Character::CaptainFalcon,
+            Character::from_str("zzcf").unwrap()
+        );
+        drop(guard);
+        assert!(parsable_enum::parse_exact::<Character>("zzcf").is_none());
+    }
+    #[test]
     fn stage_parse_from_usize() {
         let stage_from_usize = Stage::try_from(2).unwrap();
         assert_eq!(Stage::FountainOfDreams, stage_from_usize);
@@ -33,4 +180,509 @@ mod tests {
         let stage_from_usize = Stage::try_from(0);
         assert!(stage_from_usize.is_err());
     }
+    #[test]
+    fn wilson_interval_contains_winrate() {
+        let (low, high) = stats::wilson_interval(15, 20, stats::Z_95);
+        assert!(low < 0.75 && 0.75 < high);
+        assert!((low - 0.531).abs() < 0.001);
+        assert!((high - 0.888).abs() < 0.001);
+    }
+    #[test]
+    fn fisher_exact_matches_known_value() {
+        //the classic tea tasting table
+        let p = stats::fisher_exact(3, 4, 1, 4);
+        assert!((p - 0.486).abs() < 0.001);
+    }
+    #[test]
+    fn filter_parse_from_str() {
+        let filter = Filter::from_str("captain falcon vs marth on:fd code abc#123").unwrap();
+        assert_eq!(Some(Character::CaptainFalcon), filter.player_char);
+        assert_eq!(Some(Character::Marth), filter.opponent_char);
+        assert_eq!(Some(Stage::FinalDestination), filter.stage);
+        assert_eq!(Some(String::from("ABC#123")), filter.opponent_code);
+    }
+    #[test]
+    fn filter_display_parses_back() {
+        let filter = Filter {
+            player_char: Some(Character::CaptainFalcon),
+            opponent_char: Some(Character::Marth),
+            stage: Some(Stage::FinalDestination),
+            opponent_code: Some(String::from("ABC#123")),
+            since: Some(chrono::Utc.with_ymd_and_hms(2021, 3, 1, 0, 0, 0).unwrap()),
+            until: Some(chrono::Utc.with_ymd_and_hms(2021, 4, 1, 0, 0, 0).unwrap()),
+            result: Some(filter::Outcome::Loss),
+        };
+        assert_eq!(filter, Filter::from_str(&filter.to_string()).unwrap());
+    }
+    #[test]
+    fn filter_parse_missing_value() {
+        assert!(Filter::from_str("fox vs").is_err());
+    }
+    #[test]
+    fn filter_parse_dates() {
+        let now = chrono::Utc.with_ymd_and_hms(2021, 2, 15, 12, 0, 0).unwrap();
+        let march = filter::parse_date("march", now).unwrap();
+        assert_eq!(chrono::Utc.with_ymd_and_hms(2020, 3, 1, 0, 0, 0).unwrap(), march);
+        let week = filter::parse_date("1w", now).unwrap();
+        assert_eq!(chrono::Utc.with_ymd_and_hms(2021, 2, 8, 12, 0, 0).unwrap(), week);
+        for invalid in ["30é", "", "d", "9999999999999999d", "9223372036854775807y"] {
+            assert!(filter::parse_date(invalid, now).is_err());
+        }
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn command_parse_multi_word_names() {
+        match command::parse(&["character", "captain", "falcon", "matchups"]).map(|q| q.command) {
+            Ok(Command::Character(Character::CaptainFalcon, Stat::Matchups)) => {}
+            other => panic!("unexpected parse {:?}", other),
+        }
+        match command::parse(&["matchup", "ice", "climbers", "dr", "mario"]).map(|q| q.command) {
+            Ok(Command::Matchup(Character::IceClimbers, Character::DrMario)) => {}
+            other => panic!("unexpected parse {:?}", other),
+        }
+        match command::parse(&["matchup", "dr", "mario", "mario"]).map(|q| q.command) {
+            Ok(Command::Matchup(Character::DrMario, Character::Mario)) => {}
+            other => panic!("unexpected parse {:?}", other),
+        }
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn command_parse_trailing_conditions() {
+        let query =
+            command::parse(&["character", "fox", "matchups", "since:30d", "on", "fd"]).unwrap();
+        assert!(matches!(
+            query.command,
+            Command::Character(Character::Fox, Stat::Matchups)
+        ));
+        assert!(query.filter.since.is_some());
+        assert_eq!(Some(Stage::FinalDestination), query.filter.stage);
+        let query = command::parse(&["matchup", "fox", "marth", "against:abc#123"]).unwrap();
+        assert!(matches!(
+            query.command,
+            Command::Matchup(Character::Fox, Character::Marth)
+        ));
+        assert_eq!(Some(String::from("ABC#123")), query.filter.opponent_code);
+        assert!(matches!(
+            command::parse(&["character", "fox"]),
+            Err(command::CommandError::MissingArgument("stat"))
+        ));
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn command_parse_rejects_invalid_stat() {
+        assert!(command::parse(&["stage", "fd", "overview"]).is_err());
+    }
+    #[test]
+    fn game_log_sorts_pages_and_searches() {
+        let mut data = test_data();
+        assert_eq!(4, data.last(10).games.len());
+        for i in 0..30 {
+            let mut game = test_game(Character::Fox, Character::Peach, Stage::Battlefield, true);
+            game.frames = 60 * (200 + i);
+            data.add_game(game);
+        }
+        let mut log = GameLog::new();
+        log.sort = GameSort::Longest;
+        let report = data.game_log(&log);
+        assert_eq!(
+            (34, 2, 20),
+            (report.total, report.pages, report.games.len())
+        );
+        assert_eq!(60 * 229, report.games[0].game.frames);
+        log.page = 2;
+        assert_eq!(14, data.game_log(&log).games.len());
+        log.page = 3;
+        let past_end = data.game_log(&log);
+        assert!(past_end.games.is_empty());
+        assert_eq!(0, past_end.first);
+        log.page = 1;
+        log.search = Some(String::from("sheik"));
+        assert_eq!(1, data.game_log(&log).total);
+        log.page = 2;
+        let report = data.game_log(&log);
+        assert_eq!(0, report.first);
+        assert_eq!("There's no page 2, the log has 1 page.", report.to_string());
+        log.search = Some(String::from("nobody"));
+        assert_eq!(
+            (0, 0),
+            (data.game_log(&log).first, data.game_log(&log).total)
+        );
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn game_takes_a_number_or_path() {
+        let query = command::parse(&["game", "2", "vs", "marth"]).unwrap();
+        assert!(matches!(query.command, Command::Game(GameRef::Id(2))));
+        assert_eq!(Some(Character::Marth), query.filter.opponent_char);
+        let query = command::parse(&["game", "Replays/Game", "1.slp"]).unwrap();
+        assert!(matches!(
+            query.command,
+            Command::Game(GameRef::Path(ref path)) if path.to_str() == Some("Replays/Game 1.slp")
+        ));
+        let mut data = test_data();
+        let mut newest = test_game(Character::Falco, Character::Fox, Stage::YoshisStory, true);
+        newest.timestamp += chrono::Duration::days(1);
+        data.add_game(newest);
+        assert_eq!(Character::Falco, data.game(1).unwrap().player_char);
+        assert_eq!(1, data.game_log(&GameLog::new()).games[0].id);
+        assert!(data.game(0).is_none() && data.game(6).is_none());
+    }
+    #[test]
+    fn winrate_report_counts_games() {
+        let report = test_data().winrate(Character::Fox);
+        assert_eq!(3, report.record.games);
+        assert_eq!(2, report.record.wins);
+    }
+    #[test]
+    fn breakdown_report_skips_empty_rows() {
+        let report = test_data().matchups(Player::Player);
+        let keys: Vec<Character> = report.rows.iter().map(|r| r.key).collect();
+        assert_eq!(vec![Character::Marth, Character::Sheik], keys);
+    }
+    #[test]
+    fn overview_report_favorites() {
+        let report = test_data().overview();
+        assert_eq!(Character::Fox, report.characters.favorite.unwrap().key);
+        assert_eq!(Stage::Battlefield, report.stages.favorite.unwrap().key);
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn csv_output_escapes_cells() {
+        let report = test_data().stages(Character::Fox);
+        let csv = output::render(&report, OutputFormat::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            "Name,Games,Wins,Losses,Winrate,95% CI low,95% CI high",
+            lines[0]
+        );
+        assert!(lines[1].starts_with("Battlefield,2,2,0,100.00%"));
+        assert_eq!("\"a,\"\"b\"\"\"", output::csv_escape("a,\"b\""));
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn grid_colours_only_when_asked() {
+        let chart = test_data().matchup_chart();
+        let plain = output::render(&chart, OutputFormat::Text);
+        assert!(plain.contains("Fox") && !plain.contains('\x1b'));
+        let colored = output::render_colored(&chart, OutputFormat::Text, true);
+        assert!(colored.contains("\x1b[1;32m"));
+        let csv = output::render_colored(&chart, OutputFormat::Csv, true);
+        assert!(!csv.contains('\x1b'));
+    }
+    #[test]
+    fn lower_bound_ranking_prefers_larger_samples() {
+        let ranking = stats::Ranking::LowerBound;
+        assert!(ranking.best_score(40, 50) > ranking.best_score(2, 2));
+    }
+    #[test]
+    fn parse_dir_sends_progress_events() {
+        struct Events(Vec<String>);
+        impl Progress for Events {
+            fn started(&mut self, total: u64) {
+                self.0.push(format!("started {}", total));
+            }
+            fn finished(&mut self, summary: &ScanSummary) {
+                self.0.push(format!("finished {}", summary.parsed));
+            }
+        }
+        let dir = std::env::temp_dir().join("slippi_stats_progress_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a replay").unwrap();
+        let mut events = Events(Vec::new());
+        let data =
+            PlayerData::parse_dir_with(dir.clone(), String::from("TEST#1"), &dir, &mut events)
+                .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(0, data.games().len());
+        assert_eq!(vec!["started 0", "finished 0"], events.0);
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn html_report_escapes_and_keeps_path_case() {
+        assert_eq!("&lt;b&gt; &amp; &quot;", html::escape("<b> & \""));
+        let html = html::render(&test_data());
+        assert!(html.contains("<h2>Matchup chart</h2>"));
+        match command::parse(&["EXPORT", "html", "Reports/My Stats.html"]).map(|q| q.command) {
+            Ok(Command::Export(Export::Html(path))) => {
+                assert_eq!(std::path::PathBuf::from("Reports/My Stats.html"), path)
+            }
+            other => panic!("unexpected parse {:?}", other),
+        }
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn deaths_report_bins_percents() {
+        let report = test_data().deaths(Character::Fox);
+        assert_eq!(6, report.total_deaths());
+        assert_eq!(3, report.bins[4].deaths);
+        assert_eq!(3, report.bins[9].kills);
+        assert_eq!("180%+", report.bins[9].label());
+        assert_eq!(Some(100.0), report.average_death);
+        let svg = chart::histogram(&report);
+        assert!(svg.starts_with("<svg") && svg.contains("80-99%: 3 lost"));
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn server_query_parses_filter_params() {
+        let query = server::parse_query("char=captain%20falcon&code=abc%23123&limit=5");
+        assert_eq!(("char".to_string(), "captain falcon".to_string()), query[0]);
+        let mut filter = Filter::new();
+        assert!(filter.set_keyword(&query[0].0, &query[0].1).unwrap());
+        assert!(filter.set_keyword(&query[1].0, &query[1].1).unwrap());
+        assert!(!filter.set_keyword("limit", "5").unwrap());
+        assert_eq!(Some(Character::CaptainFalcon), filter.player_char);
+        assert_eq!(Some(String::from("ABC#123")), filter.opponent_code);
+        assert!(server::FILTER_PARAMS.iter().all(|p| Filter::is_keyword(p)));
+        assert!(server::FILTER_PARAMS.contains(&"result"));
+        assert!(filter.set_keyword("result", "loss").unwrap());
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn config_set_validates_and_round_trips() {
+        let path = std::env::temp_dir().join("slippi_stats_config_test.toml");
+        let mut config = Config::load(Some(path.clone())).unwrap();
+        config.set("code", "abc#123").unwrap();
+        config.set("format", "Table").unwrap();
+        config.set("prior_winrate", "40%").unwrap();
+        assert!(config.set("prior_winrate", "140").is_err());
+        assert!(config.set("colour", "red").is_err());
+        config.save().unwrap();
+        let loaded = Config::load(Some(path.clone())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let profile = loaded.active();
+        assert_eq!(Some(String::from("ABC#123")), profile.code);
+        assert_eq!(Some(OutputFormat::Table), profile.format);
+        assert_eq!(Some(40.0), profile.prior_winrate);
+    }
+    #[test]
+    #[cfg(all(feature = "cli", unix))]
+    fn config_with_non_utf8_dir_fails_to_save() {
+        use std::os::unix::ffi::OsStrExt;
+        let path = std::env::temp_dir().join("slippi_stats_config_utf8_test.toml");
+        let mut config = Config::load(Some(path.clone())).unwrap();
+        let dir = std::path::PathBuf::from(std::ffi::OsStr::from_bytes(b"replays\xff"));
+        config
+            .add_profile("odd", Profile::new(String::from("ODD#1"), dir))
+            .unwrap();
+        assert!(config.show().is_err());
+        assert!(config.save().is_err() && !path.exists());
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn saved_queries_validate_and_expand() {
+        let path = std::env::temp_dir().join("slippi_stats_query_test.toml");
+        let mut config = Config::load(Some(path)).unwrap();
+        config
+            .set_query("Weekly_Fox", "character fox matchups since:7d format:table")
+            .unwrap();
+        assert!(config.set_query("player", "player winrate").is_err());
+        assert!(config.set_query("broken", "character fox").is_err());
+        let tokens = config.expand_query(&["weekly_fox", "vs", "marth"]).unwrap();
+        let tokens: Vec<&str> = tokens.iter().map(|t| t.as_str()).collect();
+        let query = command::parse(&tokens).unwrap();
+        assert!(matches!(
+            query.command,
+            Command::Character(Character::Fox, Stat::Matchups)
+        ));
+        assert_eq!(Some(Character::Marth), query.filter.opponent_char);
+        assert_eq!(Some(OutputFormat::Table), query.format);
+        assert!(config.expand_query(&["player", "winrate"]).is_none());
+        config.remove_query("weekly_fox").unwrap();
+        assert!(config.remove_query("weekly_fox").is_err());
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn script_runs_commands_into_files() {
+        let path = std::env::temp_dir().join("slippi_stats_script_test.toml");
+        let config = Config::load(Some(path)).unwrap();
+        let report = std::env::temp_dir()
+            .join("slippi_stats_script_test")
+            .join("fox.csv");
+        let text = format!(
+            "# weekly\n\nformat csv\noutput {}\ncharacter fox winrate\noutput\n",
+            report.display()
+        );
+        let steps = script::parse(&text, &config).unwrap();
+        assert_eq!(4, steps.len());
+        script::run(&steps, &mut test_data(), OutputFormat::Text).unwrap();
+        let written = std::fs::read_to_string(&report).unwrap();
+        std::fs::remove_dir_all(report.parent().unwrap()).unwrap();
+        assert!(written.starts_with("Conditions,Games,Wins"));
+        match script::parse("player winrate\ncharacter fox\n", &config) {
+            Err(e) => assert_eq!(2, e.line),
+            Ok(_) => panic!("a missing argument should stop the script"),
+        }
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn frames_export_parses_and_writes_headers() {
+        let query = command::parse(&["export", "frames", "My", "Frames.csv", "game:2", "vs:marth"])
+            .unwrap();
+        assert!(matches!(
+            query.command,
+            Command::Export(Export::Frames(ref path, Some(2))) if path.to_str() == Some("My Frames.csv")
+        ));
+        assert_eq!(Some(Character::Marth), query.filter.opponent_char);
+        assert!(command::parse(&["export", "frames", "game:2"]).is_err());
+        let dir = std::env::temp_dir().join("slippi_stats_frames_test");
+        let data = test_data();
+        let games: Vec<&GameData> = data.games().iter().collect();
+        assert_eq!(0, frames::export(&games, &dir.join("frames.csv")).unwrap());
+        assert_eq!(
+            0,
+            frames::export(&games, &dir.join("frames.parquet")).unwrap()
+        );
+        let csv = std::fs::read_to_string(dir.join("frames.csv")).unwrap();
+        let parquet = std::fs::read(dir.join("frames.parquet")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(csv.starts_with("replay,frame,port,code,character,x,y,action_state"));
+        assert!(parquet.starts_with(b"PAR1") && parquet.ends_with(b"PAR1"));
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn dolphin_queue_exports_filtered_games() {
+        let query = command::parse(&[
+            "export",
+            "dolphin",
+            "review.json",
+            "result:loss",
+            "vs:marth",
+            "clips:deaths",
+            "below:80",
+        ])
+        .unwrap();
+        match query.command {
+            Command::Export(Export::Dolphin(path, queue)) => {
+                assert_eq!(std::path::PathBuf::from("review.json"), path);
+                assert_eq!(Some(dolphin::ClipStocks::Deaths), queue.clips);
+                assert_eq!(Some(80.0), queue.below);
+            }
+            _ => panic!("expected a dolphin export"),
+        }
+        assert_eq!(Some(filter::Outcome::Loss), query.filter.result);
+        assert!(command::parse(&["export", "dolphin", "review.json", "below:80"]).is_err());
+        let mut data = test_data();
+        data.retain(&query.filter);
+        assert_eq!(2, data.games().len());
+        let path = std::env::temp_dir().join("slippi_stats_dolphin_test.json");
+        let games: Vec<&GameData> = data.games().iter().collect();
+        let queued = dolphin::export(&games, &dolphin::Queue::default(), "TEST#1", &path).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(2, queued);
+        assert_eq!("queue", written["mode"]);
+        assert!(written["queue"][0]["path"]
+            .as_str()
+            .unwrap()
+            .ends_with(".slp"));
+        assert!(written["queue"][0].get("startFrame").is_none());
+        let stock = |frame, lost_by: &str, percent| StockLoss {
+            frame,
+            lost_by: lost_by.to_string(),
+            stocks_left: 3,
+            percent,
+            killed_by: None,
+        };
+        let stocks = [
+            stock(100, "TEST#1", 40.0),
+            stock(3000, "OPP#1", 120.0),
+            stock(5000, "TEST#1", 95.0),
+            stock(5990, "TEST#1", 60.0),
+        ];
+        let mut queue = dolphin::Queue {
+            clips: Some(dolphin::ClipStocks::Deaths),
+            below: Some(80.0),
+            ..dolphin::Queue::default()
+        };
+        let deaths =
+            dolphin::clip_frames(&stocks, dolphin::ClipStocks::Deaths, &queue, "TEST#1", 6000);
+        //the first clip can't start before the replay does, or the last end after it
+        assert_eq!(
+            vec![(FIRST_FRAME, -23 + 120), (5867 - 480, 6000 - 124)],
+            deaths
+        );
+        queue.below = None;
+        queue.above = Some(100.0);
+        let kills =
+            dolphin::clip_frames(&stocks, dolphin::ClipStocks::Kills, &queue, "TEST#1", 6000);
+        assert_eq!(vec![(2877 - 480, 2877 + 120)], kills);
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn removing_active_profile_falls_back() {
+        let path = std::env::temp_dir().join("slippi_stats_profile_test.toml");
+        let mut config = Config::load(Some(path)).unwrap();
+        let dir = std::path::PathBuf::from("replays");
+        config
+            .add_profile("alice", Profile::new(String::from("ALI#1"), dir.clone()))
+            .unwrap();
+        config
+            .add_profile("bob", Profile::new(String::from("BOB#2"), dir))
+            .unwrap();
+        assert!(config.add_profile("bob", Profile::default()).is_err());
+        config.switch("bob").unwrap();
+        assert_eq!(Some(String::from("BOB#2")), config.active().code);
+        config.remove_profile("bob").unwrap();
+        assert_eq!("alice", config.active_name());
+        assert!(config.switch("bob").is_err());
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn repl_runs_one_line_profile_commands() {
+        let path = std::env::temp_dir().join("slippi_stats_repl_profile_test.toml");
+        let mut config = Config::load(Some(path.clone())).unwrap();
+        let dir = std::path::PathBuf::from("replays");
+        config
+            .add_profile("alice", Profile::new(String::from("ALI#1"), dir.clone()))
+            .unwrap();
+        config
+            .add_profile("bob", Profile::new(String::from("BOB#2"), dir))
+            .unwrap();
+        let reload = input::profiles(
+            &mut config,
+            &["profile", "switch", "bob"],
+            OutputFormat::Text,
+        );
+        let saved = Config::load(Some(path.clone())).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(reload);
+        assert_eq!("bob", config.active_name());
+        assert_eq!("bob", saved.active_name());
+        assert!(!input::profiles(
+            &mut config,
+            &["profile", "switch", "carol"],
+            OutputFormat::Text
+        ));
+        assert_eq!(
+            ConfigCommand::Set(String::from("format"), String::from("table")),
+            ConfigCommand::parse(&["set", "FORMAT", "table"]).unwrap()
+        );
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn sql_query_reads_exported_tables() {
+        let sql = "SELECT player_character, count(*), sum(result = 'win') FROM games \
+                   GROUP BY player_character ORDER BY player_character";
+        let report = database::query(&test_data(), sql).unwrap();
+        assert_eq!(vec!["Falco", "1", "0"], report.rows[0]);
+        assert_eq!(vec!["Fox", "3", "2"], report.rows[1]);
+        let stocks = database::query(&test_data(), "SELECT count(*) FROM stocks").unwrap();
+        assert_eq!("20", stocks.rows[0][0]);
+    }
+    #[test]
+    #[cfg(feature = "cli")]
+    fn sqlite_export_only_replaces_databases() {
+        let dir = std::env::temp_dir().join("slippi_stats_sqlite_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stats.db");
+        let notes = dir.join("notes.txt");
+        std::fs::write(&notes, "keep me").unwrap();
+        database::export(&test_data(), &path).unwrap();
+        database::export(&test_data(), &path).unwrap();
+        assert!(database::export(&test_data(), &notes).is_err());
+        let kept = std::fs::read_to_string(&notes).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!("keep me", kept);
+    }
 }
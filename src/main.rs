@@ -1,10 +1,7 @@
-use slippi_stats::input;
+use slippi_stats::cli;
+use std::env;
+use std::process;
 
 fn main() {
-    loop {
-        let results = input::load_data();
-        if input::main_loop(results) {
-            break;
-        }
-    }
+    process::exit(cli::run(env::args().skip(1)));
 }
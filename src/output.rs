@@ -0,0 +1,541 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Display;
+use std::io::{self, IsTerminal, Write};
+use std::str::FromStr;
+
+use crate::gamedata;
+use crate::rating::{RatingHistory, Ratings};
+use crate::report::{
+    BreakdownReport, CompareReport, DeathsReport, FavBestReport, GameDetailReport, GameLogReport,
+    GamesReport, GridReport, LoggedGame, OverviewReport, PlayerDetail, QueryReport,
+    RatingHistoryReport, SessionsReport, TrendReport, WinLossData, WinrateReport,
+};
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    //the plain sentences the program has always printed
+    #[default]
+    Text,
+    Table,
+    Json,
+    Csv,
+    Markdown,
+}
+
+#[derive(Debug)]
+pub struct OutputFormatParseError(String);
+
+pub struct Table {
+    pub title: String,
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+//anything that can be printed in every output format
+pub trait Report: Serialize + Display {
+    fn to_table(&self) -> Table;
+}
+
+pub fn render<R: Report>(report: &R, format: OutputFormat) -> String {
+    render_colored(report, format, false)
+}
+
+//text reports colour their winrates when `color` is set
+pub fn render_colored<R: Report>(report: &R, format: OutputFormat, color: bool) -> String {
+    match format {
+        OutputFormat::Text if color => format!("{:#}", report),
+        OutputFormat::Text => format!("{}", report),
+        OutputFormat::Table => report.to_table().pretty(),
+        //reports only hold plain data, so this can't fail
+        OutputFormat::Json => serde_json::to_string_pretty(report).unwrap(),
+        OutputFormat::Csv => report.to_table().csv(),
+        OutputFormat::Markdown => report.to_table().markdown(),
+    }
+}
+
+pub fn print<R: Report>(report: &R, format: OutputFormat) {
+    println!(
+        "{}",
+        render_colored(report, format, io::stdout().is_terminal())
+    );
+}
+
+//plain text, for files and scripts
+pub fn write<R: Report, W: Write + ?Sized>(
+    out: &mut W,
+    report: &R,
+    format: OutputFormat,
+) -> io::Result<()> {
+    writeln!(out, "{}", render(report, format))
+}
+
+pub fn write_colored<R: Report, W: Write + ?Sized>(
+    out: &mut W,
+    report: &R,
+    format: OutputFormat,
+    color: bool,
+) -> io::Result<()> {
+    writeln!(out, "{}", render_colored(report, format, color))
+}
+
+impl Table {
+    fn new(title: String, headers: &[&str]) -> Self {
+        Self {
+            title,
+            headers: headers.iter().map(|h| h.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    fn widths(&self) -> Vec<usize> {
+        (0..self.headers.len())
+            .map(|i| {
+                self.rows
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain(std::iter::once(self.headers[i].chars().count()))
+                    .max()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    pub fn pretty(&self) -> String {
+        let widths = self.widths();
+        let border: Vec<String> = widths.iter().map(|w| "-".repeat(w + 2)).collect();
+        let border = format!("+{}+", border.join("+"));
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, w)| format!(" {:w$} ", cell, w = w))
+                .collect();
+            format!("|{}|", cells.join("|"))
+        };
+        let mut output = vec![self.title.clone(), border.clone(), line(&self.headers)];
+        output.push(border.clone());
+        for row in &self.rows {
+            output.push(line(row));
+        }
+        output.push(border);
+        output.join("\n")
+    }
+
+    pub fn csv(&self) -> String {
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|c| csv_escape(c)).collect();
+            cells.join(",")
+        };
+        let mut output = vec![line(&self.headers)];
+        for row in &self.rows {
+            output.push(line(row));
+        }
+        output.join("\n")
+    }
+
+    pub fn markdown(&self) -> String {
+        let line = |cells: &[String]| {
+            let cells: Vec<String> = cells.iter().map(|c| c.replace('|', "\\|")).collect();
+            format!("| {} |", cells.join(" | "))
+        };
+        let divider: Vec<&str> = self.headers.iter().map(|_| "---").collect();
+        let mut output = vec![
+            format!("**{}**", self.title),
+            String::new(),
+            line(&self.headers),
+            format!("| {} |", divider.join(" | ")),
+        ];
+        for row in &self.rows {
+            output.push(line(row));
+        }
+        output.join("\n")
+    }
+}
+
+pub fn csv_escape(cell: &str) -> String {
+    if cell.contains(',') || cell.contains('"') || cell.contains('\n') {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+const RECORD_HEADERS: [&str; 6] = [
+    "Games",
+    "Wins",
+    "Losses",
+    "Winrate",
+    "95% CI low",
+    "95% CI high",
+];
+
+fn record_cells(record: &WinLossData) -> Vec<String> {
+    let mut cells = vec![
+        record.games.to_string(),
+        record.wins.to_string(),
+        (record.games - record.wins).to_string(),
+    ];
+    if record.games == 0 {
+        cells.extend(vec![String::from("-"); 3]);
+    } else {
+        let (low, high) = record.interval();
+        cells.push(format!("{:.2}%", record.winrate()));
+        cells.push(format!("{:.2}%", low));
+        cells.push(format!("{:.2}%", high));
+    }
+    cells
+}
+
+fn headers_with_record(first: &[&'static str]) -> Vec<&'static str> {
+    first.iter().chain(RECORD_HEADERS.iter()).copied().collect()
+}
+
+impl Report for WinrateReport {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(self.label.clone(), &headers_with_record(&["Conditions"]));
+        let mut row = vec![self.label.clone()];
+        row.extend(record_cells(&self.record));
+        table.rows.push(row);
+        table
+    }
+}
+
+impl<T: Serialize + Display> Report for BreakdownReport<T> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(self.label.clone(), &headers_with_record(&["Name"]));
+        for row in &self.rows {
+            let mut cells = vec![format!("{}", row.key)];
+            cells.extend(record_cells(&row.record));
+            table.rows.push(cells);
+        }
+        table
+    }
+}
+
+impl<R: Serialize + Display, C: Serialize + Display> Report for GridReport<R, C> {
+    fn to_table(&self) -> Table {
+        let mut headers = vec![String::new()];
+        headers.extend(self.columns.iter().map(|c| format!("{}", c)));
+        let rows = self
+            .rows
+            .iter()
+            .zip(&self.cells)
+            .map(|(row, cells)| {
+                let mut line = vec![format!("{}", row)];
+                line.extend(cells.iter().map(Self::cell_text));
+                line
+            })
+            .collect();
+        Table {
+            title: self.label.clone(),
+            headers,
+            rows,
+        }
+    }
+}
+
+impl<T: Display> FavBestReport<T> {
+    fn table_rows(&self) -> Vec<Vec<String>> {
+        let labels = self.data_type.labels();
+        let mut rows = Vec::new();
+        for (label, row) in [
+            (labels.0, &self.favorite),
+            (labels.1, &self.best),
+            (labels.2, &self.worst),
+        ]
+        .iter()
+        {
+            if let Some(row) = row {
+                let mut cells = vec![label.to_string(), format!("{}", row.key)];
+                cells.extend(record_cells(&row.record));
+                rows.push(cells);
+            }
+        }
+        rows
+    }
+}
+
+impl Report for OverviewReport {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(
+            format!("Overview (ranked by {})", self.ranking),
+            &headers_with_record(&["Category", "Pick"]),
+        );
+        table.rows.extend(self.characters.table_rows());
+        table.rows.extend(self.opponents.table_rows());
+        table.rows.extend(self.stages.table_rows());
+        table
+    }
+}
+
+impl Report for CompareReport {
+    fn to_table(&self) -> Table {
+        let title = match &self.difference {
+            Some(d) => format!(
+                "Difference {:+.2}% (95% CI {:+.2}% to {:+.2}%), p = {:.3} (Fisher's exact test)",
+                d.winrate, d.interval.0, d.interval.1, d.p_value
+            ),
+            None => String::from("Not enough data to compare"),
+        };
+        let mut table = Table::new(title, &headers_with_record(&["Conditions"]));
+        for report in [&self.first, &self.second].iter() {
+            let mut row = vec![report.label.clone()];
+            row.extend(record_cells(&report.record));
+            table.rows.push(row);
+        }
+        table
+    }
+}
+
+impl Report for GamesReport<'_> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(
+            format!("{} games", self.games.len()),
+            &[
+                "Date",
+                "Character",
+                "Opponent",
+                "Stage",
+                "Opponent code",
+                "Result",
+            ],
+        );
+        for game in &self.games {
+            table.rows.push(vec![
+                game.timestamp.format("%Y-%m-%d %H:%M").to_string(),
+                format!("{}", game.player_char),
+                format!("{}", game.opponent_char),
+                format!("{}", game.stage),
+                game.opponent_code.clone(),
+                format!("{}", game.match_result),
+            ]);
+        }
+        table
+    }
+}
+
+impl Report for GameLogReport<'_> {
+    fn to_table(&self) -> Table {
+        let title = match self.games.len() {
+            0 => format!("0 of {} games", self.total),
+            n => format!(
+                "Games {}-{} of {}",
+                self.first,
+                self.first + n - 1,
+                self.total
+            ),
+        };
+        let mut table = Table::new(
+            title,
+            &[
+                "#",
+                "Date",
+                "Opponent code",
+                "Character",
+                "Opponent",
+                "Stage",
+                "Result",
+                "Length",
+                "Replay",
+            ],
+        );
+        for LoggedGame { id, game } in &self.games {
+            table.rows.push(vec![
+                id.to_string(),
+                game.timestamp.format("%Y-%m-%d %H:%M").to_string(),
+                game.opponent_code.clone(),
+                format!("{}", game.player_char),
+                format!("{}", game.opponent_char),
+                format!("{}", game.stage),
+                format!("{}", game.match_result),
+                game.length(),
+                game.path.display().to_string(),
+            ]);
+        }
+        table
+    }
+}
+
+//one column for each player
+impl Report for GameDetailReport {
+    fn to_table(&self) -> Table {
+        let [player, opponent] = &self.players;
+        let mut table = Table::new(
+            format!("{} {}", self.game, self.game.length()),
+            &["", &player.code, &opponent.code],
+        );
+        let mut row = |label: &str, cell: &dyn Fn(&PlayerDetail) -> String| {
+            table
+                .rows
+                .push(vec![label.to_string(), cell(player), cell(opponent)]);
+        };
+        row("Character", &|p| format!("{}", p.character));
+        row("Damage dealt", &|p| format!("{:.1}", p.damage_dealt));
+        row("Damage taken", &|p| format!("{:.1}", p.damage_taken));
+        row("Openings", &|p| p.openings.to_string());
+        row("Openings per kill", &|p| {
+            p.openings_per_kill()
+                .map_or(String::from("-"), |o| format!("{:.1}", o))
+        });
+        row("Kill moves", &|p| p.kill_moves.join(", "));
+        row("Stocks lost", &|p| {
+            let stocks: Vec<String> = self
+                .stocks
+                .iter()
+                .filter(|s| s.lost_by == p.code)
+                .map(|s| format!("{} at {:.1}%", gamedata::clock(s.frame), s.percent))
+                .collect();
+            stocks.join(", ")
+        });
+        row("L-cancels", &|p| p.l_cancel_text());
+        row("APM", &|p| format!("{:.0}", p.apm));
+        table
+    }
+}
+
+impl Report for SessionsReport {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(
+            format!("{} sessions", self.sessions.len()),
+            &headers_with_record(&["Start", "End"]),
+        );
+        table.headers.push(String::from("Opponents"));
+        for session in &self.sessions {
+            let mut row = vec![
+                session.start.format("%Y-%m-%d %H:%M").to_string(),
+                session.end.format("%Y-%m-%d %H:%M").to_string(),
+            ];
+            row.extend(record_cells(&session.record));
+            row.push(session.opponents.join(" "));
+            table.rows.push(row);
+        }
+        table
+    }
+}
+
+impl Report for TrendReport {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(self.label.clone(), &headers_with_record(&["Week of"]));
+        for point in &self.points {
+            let mut row = vec![point.start.to_string()];
+            row.extend(record_cells(&point.record));
+            table.rows.push(row);
+        }
+        table
+    }
+}
+
+impl Report for DeathsReport {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(
+            self.label.clone(),
+            &["Percent", "Stocks lost", "Stocks taken"],
+        );
+        for bin in &self.bins {
+            table.rows.push(vec![
+                bin.label(),
+                bin.deaths.to_string(),
+                bin.kills.to_string(),
+            ]);
+        }
+        table
+    }
+}
+
+impl Report for QueryReport {
+    fn to_table(&self) -> Table {
+        Table {
+            title: format!("{} rows", self.rows.len()),
+            headers: self.headers.clone(),
+            rows: self.rows.clone(),
+        }
+    }
+}
+
+fn history_table(title: String, history: &RatingHistory) -> Table {
+    let mut table = Table::new(
+        title,
+        &["Date", "Rating", "Deviation", "Volatility", "Games"],
+    );
+    for snapshot in &history.snapshots {
+        table.rows.push(vec![
+            snapshot.date.to_string(),
+            format!("{:.0}", snapshot.rating.rating),
+            format!("{:.0}", snapshot.rating.deviation),
+            format!("{:.4}", snapshot.rating.volatility),
+            snapshot.games.to_string(),
+        ]);
+    }
+    table
+}
+
+impl Report for RatingHistoryReport {
+    fn to_table(&self) -> Table {
+        history_table(self.label.clone(), &self.history)
+    }
+}
+
+impl Report for Ratings {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new(
+            String::from("Current ratings"),
+            &["Name", "Rating", "Deviation", "Games", "Last played"],
+        );
+        let overall = (String::from("Overall"), &self.overall);
+        let characters = self
+            .characters
+            .iter()
+            .map(|(c, history)| (format!("{}", c), history));
+        for (name, history) in std::iter::once(overall).chain(characters) {
+            if let Some(current) = history.current() {
+                table.rows.push(vec![
+                    name,
+                    format!("{:.0}", current.rating.rating),
+                    format!("{:.0}", current.rating.deviation),
+                    current.games.to_string(),
+                    current.date.to_string(),
+                ]);
+            }
+        }
+        table
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = OutputFormatParseError;
+    fn from_str(arg: &str) -> Result<Self, Self::Err> {
+        match arg {
+            "text" => Ok(OutputFormat::Text),
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "markdown" | "md" => Ok(OutputFormat::Markdown),
+            _ => Err(OutputFormatParseError(arg.to_string())),
+        }
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let text = match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Table => "table",
+            OutputFormat::Json => "json",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Markdown => "markdown",
+        };
+        write!(f, "{}", text)
+    }
+}
+
+impl Display for OutputFormatParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not an output format. Use text, table, json, csv or markdown.",
+            self.0
+        )
+    }
+}
@@ -1,45 +1,143 @@
+use crate::fuzzy::{self, Match};
 use crate::gamedata::GameData;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::TryFrom;
+use std::fmt;
 use std::fmt::Display;
 use std::str::FromStr;
+use std::sync::RwLock;
 
 //these traits should probably be somewhere else but i'm not sure where yet
 pub trait Numbered {
     const NUM_VALUES: usize;
 }
-pub trait Parsable: FromStr + Display + TryFrom<usize> {}
+pub trait Parsable: FromStr + Display + TryFrom<usize> + Copy + PartialEq + 'static {
+    //what the values are called in messages and the config, like `character`
+    const KIND: &'static str;
+    //display names and the built in aliases
+    fn names() -> &'static [(&'static str, Self)];
+    fn number(self) -> usize;
+}
 pub trait GameDataCondition {
     fn game_data_condition(&self, game: &GameData) -> bool;
 }
 
 #[derive(Debug)]
 pub enum ParsableEnumError {
-    FromStrError(String),
+    FromStrError {
+        kind: &'static str,
+        input: String,
+        suggestions: Vec<String>,
+    },
     TryFromError(usize),
 }
 
+//aliases added at runtime (from the config), by kind and then alias, pointing at the value's number
+static ALIASES: RwLock<BTreeMap<&'static str, HashMap<String, usize>>> =
+    RwLock::new(BTreeMap::new());
+
+pub fn add_alias<T: Parsable>(alias: &str, value: T) {
+    let mut aliases = ALIASES.write().unwrap();
+    aliases
+        .entry(T::KIND)
+        .or_default()
+        .insert(alias.trim().to_lowercase(), value.number());
+}
+
+pub fn remove_alias<T: Parsable>(alias: &str) {
+    if let Some(aliases) = ALIASES.write().unwrap().get_mut(T::KIND) {
+        aliases.remove(&alias.trim().to_lowercase());
+    }
+}
+
+pub fn clear_aliases() {
+    ALIASES.write().unwrap().clear();
+}
+
+fn all_names<T: Parsable>() -> Vec<(String, T)> {
+    let mut names: Vec<(String, T)> = T::names()
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), *value))
+        .collect();
+    if let Some(aliases) = ALIASES.read().unwrap().get(T::KIND) {
+        for (alias, num) in aliases {
+            if let Ok(value) = T::try_from(*num) {
+                names.push((alias.clone(), value));
+            }
+        }
+    }
+    names
+}
+
+//only a full name or alias, for when several names are split out of the same text
+pub fn parse_exact<T: Parsable>(arg: &str) -> Option<T> {
+    let arg = arg.trim().to_lowercase();
+    all_names::<T>()
+        .into_iter()
+        .find(|(name, _)| *name == arg)
+        .map(|(_, value)| value)
+}
+
+//what `FromStr` does for every parsable enum: exact names and aliases, then prefixes and typos
+pub fn parse<T: Parsable>(arg: &str) -> Result<T, ParsableEnumError> {
+    match fuzzy::find(arg, &all_names::<T>()) {
+        Match::Found(value) => Ok(value),
+        Match::NotFound(suggestions) => Err(ParsableEnumError::FromStrError {
+            kind: T::KIND,
+            input: arg.trim().to_string(),
+            suggestions: suggestions.iter().map(|v| format!("{}", v)).collect(),
+        }),
+    }
+}
+
+impl fmt::Display for ParsableEnumError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParsableEnumError::FromStrError {
+                kind,
+                input,
+                suggestions,
+            } => {
+                write!(f, "`{}` is not a {}.", input, kind.to_lowercase())?;
+                match &suggestions[..] {
+                    [] => Ok(()),
+                    [only] => write!(f, " Did you mean {}?", only),
+                    [rest @ .., last] => {
+                        write!(f, " Did you mean {} or {}?", rest.join(", "), last)
+                    }
+                }
+            }
+            ParsableEnumError::TryFromError(num) => write!(f, "{} is out of range.", num),
+        }
+    }
+}
+
 #[macro_export]
 macro_rules! parsable_enum {
     ($vis:vis enum $name:ident{
         $($disp_name:literal; $($alias:literal,)* => $val:ident = $num_val:expr,)*
     }) => {
-        use crate::parsable_enum::ParsableEnumError;
+        use $crate::parsable_enum::ParsableEnumError;
         use serde::{Deserialize, Serialize};
         #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
         $vis enum $name {
             $($val = $num_val,)*
         }
 
-        impl crate::parsable_enum::Parsable for $name{}
+        impl $crate::parsable_enum::Parsable for $name {
+            const KIND: &'static str = stringify!($name);
+            fn names() -> &'static [(&'static str, Self)] {
+                &[$(($disp_name, $name::$val), $(($alias, $name::$val),)*)*]
+            }
+            fn number(self) -> usize {
+                self as usize
+            }
+        }
 
         impl std::str::FromStr for $name {
             type Err = ParsableEnumError;
             fn from_str(arg: &str) -> Result<Self, Self::Err> {
-                match &(arg.to_string())[..]{
-                    $(x if x == $disp_name.to_lowercase() => Ok($name::$val),
-                    $($alias => Ok($name::$val),)*)*
-                _ => Err(ParsableEnumError::FromStrError(String::from(arg)))
-                }
+                $crate::parsable_enum::parse(arg)
             }
         }
 
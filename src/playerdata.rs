@@ -1,15 +1,18 @@
 use std::fmt;
 use std::fs;
+use std::io;
 use std::time::Instant;
 
 use std::path::Path;
 use std::path::PathBuf;
 
+use chrono::{Datelike, Duration};
 use serde::{Deserialize, Serialize};
 
-use indicatif::{HumanDuration, ProgressBar, ProgressStyle};
-
-use crate::gamedata::GameData;
+use crate::cache::Cache;
+use crate::filter::Filter;
+use crate::gamedata::{GameData, GameParseError};
+use crate::gamelog::{GameLog, PAGE_SIZE};
 
 use crate::character::Character;
 use crate::stage::Stage;
@@ -17,29 +20,36 @@ use crate::stage::Stage;
 use std::fmt::Display;
 
 use crate::parsable_enum::{GameDataCondition, Numbered, Parsable};
-
-#[derive(Debug, Serialize, Deserialize)]
+#[cfg(feature = "progress")]
+use crate::progress::Bar;
+#[cfg(not(feature = "progress"))]
+use crate::progress::ErrorLog;
+use crate::progress::{Progress, ScanSummary, SkipReason};
+use crate::rating::{RatingHistory, Ratings};
+use crate::report::{
+    BreakdownReport, BreakdownRow, CompareReport, DataType, DeathsReport, FavBestReport,
+    GameLogReport, GamesReport, GridReport, LoggedGame, OverviewReport, PercentBin,
+    RatingHistoryReport, Session, SessionsReport, TrendPoint, TrendReport, WinLossData,
+    WinrateReport,
+};
+use crate::stats::Ranking;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PlayerData {
     cache_ver: usize,
+    //the netplay code the games were loaded for
+    #[serde(default)]
+    code: String,
     results: Vec<GameData>,
-}
-
-enum DataType {
-    Stages,
-    Characters,
-    Opponents,
+    #[serde(skip)]
+    ranking: Ranking,
 }
 
 #[derive(Debug)]
 struct FavBestData {
-    favorite: usize,
-    best: usize,
-}
-
-#[derive(Clone)]
-struct WinLossData {
-    games: usize,
-    wins: usize,
+    favorite: Option<usize>,
+    best: Option<usize>,
+    worst: Option<usize>,
 }
 
 type AssociatedTryFromError<T> = <T as std::convert::TryFrom<usize>>::Error;
@@ -52,33 +62,13 @@ where
     parser: fn(usize) -> Result<T, T::Error>,
 }
 
-impl WinLossData {
-    fn new() -> WinLossData {
-        Self { games: 0, wins: 0 }
-    }
-
-    fn add_game(&mut self, is_win: bool) {
-        self.games += 1;
-        if is_win {
-            self.wins += 1;
-        }
-    }
-
-    fn winrate(&self) -> f64 {
-        (self.wins as f64) / (self.games as f64) * 100.0
-    }
-}
-
-impl Display for WinLossData {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "Won {} of {} games. ({:.2}%).",
-            self.wins,
-            self.games,
-            self.winrate()
-        )
-    }
+struct WinLossGrid<R: Parsable + Numbered, C: Parsable + Numbered>
+where
+    AssociatedTryFromError<R>: std::fmt::Debug,
+    AssociatedTryFromError<C>: std::fmt::Debug,
+{
+    rows: Vec<WinLossVec<C>>,
+    row_parser: fn(usize) -> Result<R, R::Error>,
 }
 
 impl<T: Parsable + Numbered> WinLossVec<T>
@@ -105,152 +95,219 @@ where
         tot_games == 0
     }
 
-    fn fav_best(&self) -> FavBestData {
-        let mut favorite = 0;
-        let mut best = 2; //default this to 0 because the way things are set up right now this causes issues with low game counts as stage 0 does not exist
-        let mut best_winrate = 0.0;
+    fn row(&self, i: usize) -> BreakdownRow<T> {
+        let parser = self.parser;
+        BreakdownRow {
+            //unwrap cause if the parser returns an error then something went wrong somewhere else
+            key: parser(i).unwrap(),
+            record: self.data[i],
+        }
+    }
+
+    fn fav_best(&self, ranking: Ranking) -> FavBestData {
+        let mut favorite: Option<usize> = None;
+        let mut best: Option<(usize, f64)> = None;
+        let mut worst: Option<(usize, f64)> = None;
         for i in 0..self.data.len() {
-            if self.data[i].games > self.data[favorite].games {
-                favorite = i;
+            let current = &self.data[i];
+            if current.games == 0 {
+                continue;
             }
-            let current_winrate = self.data[i].winrate();
-            if (self.data[i].games > 20) && (current_winrate > best_winrate) {
-                //min 20 games so things with 1 game and 1 win don't end up taking the spot (will change this to be percent based once I decide on an appropriate percent)
-                best = i;
-                best_winrate = current_winrate;
+            if favorite.is_none_or(|fav| current.games > self.data[fav].games) {
+                favorite = Some(i);
+            }
+            let best_score = ranking.best_score(current.wins, current.games);
+            if best.is_none_or(|(_, score)| best_score > score) {
+                best = Some((i, best_score));
+            }
+            let worst_score = ranking.worst_score(current.wins, current.games);
+            if worst.is_none_or(|(_, score)| worst_score < score) {
+                worst = Some((i, worst_score));
             }
         }
-        FavBestData { favorite, best }
+        FavBestData {
+            favorite,
+            best: best.map(|(i, _)| i),
+            worst: worst.map(|(i, _)| i),
+        }
     }
 
-    fn print_fb_data(&self, d_type: DataType, fb: FavBestData) {
-        let data_labels = match d_type {
-            DataType::Characters => ("Favorite character", "Best character"),
-            DataType::Opponents => ("Most common opponent", "Easiest opponent"),
-            DataType::Stages => ("Most played stage", "Best stage"),
-        };
-        let parser = self.parser;
-        println!(
-            "{}: {} ({} games)",
-            data_labels.0,
-            parser(fb.favorite).unwrap(),
-            self.data[fb.favorite].games
-        );
-        println!(
-            "{}: {} ({:.2}% winrate)",
-            data_labels.1,
-            parser(fb.best).unwrap(),
-            self.data[fb.best].winrate()
-        );
+    fn fav_best_report(&self, data_type: DataType, ranking: Ranking) -> FavBestReport<T> {
+        let fb = self.fav_best(ranking);
+        FavBestReport {
+            data_type,
+            favorite: fb.favorite.map(|i| self.row(i)),
+            best: fb.best.map(|i| self.row(i)),
+            worst: fb.worst.map(|i| self.row(i)),
+        }
+    }
+
+    fn into_report(self, label: String) -> BreakdownReport<T> {
+        let rows = (0..self.data.len())
+            .filter(|&i| self.data[i].games > 0)
+            .map(|i| self.row(i))
+            .collect();
+        BreakdownReport { label, rows }
     }
 }
 
-impl<T: Parsable + Numbered> Display for WinLossVec<T>
+impl<R: Parsable + Numbered, C: Parsable + Numbered> WinLossGrid<R, C>
 where
-    AssociatedTryFromError<T>: std::fmt::Debug,
+    AssociatedTryFromError<R>: std::fmt::Debug,
+    AssociatedTryFromError<C>: std::fmt::Debug,
 {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if self.is_empty() {
-            return write!(f, "No data for given input.");
+    fn new() -> Self {
+        Self {
+            rows: (0..R::NUM_VALUES).map(|_| WinLossVec::new()).collect(),
+            row_parser: R::try_from,
         }
-        let mut output = String::from("");
-        for i in 0..self.data.len() {
-            if self.data[i].games == 0 {
-                continue;
-            }
-            output.push_str(&format!("{}: {}\n", T::try_from(i).unwrap(), self.data[i]));
-            //unwrap cause if the parser returns an error then something went wrong somewhere else
+    }
+
+    fn add_game(&mut self, is_win: bool, row_num: usize, col_num: usize) {
+        self.rows[row_num].add_game(is_win, col_num)
+    }
+
+    fn into_report(self, label: String) -> GridReport<R, C> {
+        //empty rows and columns are left out entirely so the chart only covers what's actually been played
+        let rows: Vec<usize> = (0..self.rows.len())
+            .filter(|&r| !self.rows[r].is_empty())
+            .collect();
+        let cols: Vec<usize> = (0..C::NUM_VALUES)
+            .filter(|&c| self.rows.iter().any(|row| row.data[c].games > 0))
+            .collect();
+        let row_parser = self.row_parser;
+        GridReport {
+            label,
+            rows: rows.iter().map(|&r| row_parser(r).unwrap()).collect(),
+            columns: cols.iter().map(|&c| C::try_from(c).unwrap()).collect(),
+            cells: rows
+                .iter()
+                .map(|&r| cols.iter().map(|&c| self.rows[r].data[c]).collect())
+                .collect(),
         }
-        write!(f, "{}", output)
     }
 }
 
 impl PlayerData {
-    const CACHE_VER: usize = 8;
+    const CACHE_VER: usize = 12;
+    //games further apart than this are counted as separate sessions
+    const SESSION_GAP_MINUTES: i64 = 60;
+    const PERCENT_BIN_WIDTH: u32 = 20;
+    const PERCENT_BINS: usize = 10;
     pub fn new() -> Self {
         Self {
             results: Vec::new(),
             cache_ver: PlayerData::CACHE_VER,
+            code: String::new(),
+            ranking: Ranking::default(),
         }
     }
 
-    pub fn parse_dir(p: PathBuf, np_code: String) -> Self {
-        let mut cache_path = String::from(p.as_path().to_str().unwrap());
-        cache_path.push_str(&format!("/{}.cache", np_code));
-        let mut cache = match fs::read_to_string(&cache_path) {
-            Ok(c) => c,
-            Err(_) => "".to_string(),
-        };
-        let mut results: PlayerData = match serde_json::from_str(&cache) {
-            Ok(gr) => gr,
-            Err(_) => PlayerData::new(),
-        };
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn games(&self) -> &[GameData] {
+        &self.results
+    }
+
+    pub fn set_ranking(&mut self, ranking: Ranking) {
+        self.ranking = ranking;
+    }
+
+    //scans with the progress bar when the `progress` feature is on, otherwise only errors are printed.
+    //the cache is kept in the replay directory
+    pub fn parse_dir(p: PathBuf, np_code: String) -> io::Result<Self> {
+        #[cfg(feature = "progress")]
+        let mut progress = Bar::new();
+        #[cfg(not(feature = "progress"))]
+        let mut progress = ErrorLog;
+        let cache_dir = p.clone();
+        PlayerData::parse_dir_with(p, np_code, &cache_dir, &mut progress)
+    }
+
+    pub fn parse_dir_with<P: Progress>(
+        p: PathBuf,
+        np_code: String,
+        cache_dir: &Path,
+        progress: &mut P,
+    ) -> io::Result<Self> {
+        let start = Instant::now();
+        let mut cache = Cache::open(cache_dir, &np_code);
+        let mut results = cache.load().unwrap_or_default();
 
         if results.cache_ver != PlayerData::CACHE_VER {
-            println!("Cache detected but out of date. Rebuilding.");
+            progress.cache_outdated();
             results = PlayerData::new();
-            cache = "".to_string();
+            cache.clear();
         }
+        results.code = np_code.clone();
 
-        let total = count_replays(&p);
-        let pb = ProgressBar::new(total);
-        let start = Instant::now();
-        pb.set_style(
-            ProgressStyle::default_bar().template(
-                "[{elapsed_precise}] [{wide_bar:.green/white}] {pos}/{len} ({eta_precise})",
-            ),
-        );
-        for entry in fs::read_dir(p).unwrap() {
-            let path = entry.unwrap().path();
-            if path.extension().unwrap() != "slp" {
+        let mut summary = ScanSummary {
+            total: count_replays(&p)?,
+            ..ScanSummary::default()
+        };
+        progress.started(summary.total);
+        for entry in fs::read_dir(p)? {
+            let path = entry?.path();
+            if !is_replay(&path) {
                 continue;
             }
             let game_metadata = match GameData::get_game_data(&path, true) {
                 Ok(gd) => gd,
                 Err(e) => {
-                    pb.println(format!("Error {:?} when parsing game {:?}", e, path));
-                    pb.inc(1);
+                    summary.failed += 1;
+                    progress.failed(&path, &e);
                     continue;
                 }
             };
-            let dt = serde_json::to_string(&game_metadata.metadata.date.unwrap()).unwrap();
-            if cache.contains(&dt) {
-                //not sure what's better: this, or loading the deserialized data and then iterating through it and checking each gamedata
-                pb.inc(1);
+            if cache.contains(&game_metadata.metadata.date.unwrap()) {
+                summary.cached += 1;
+                progress.skipped(&path, SkipReason::Cached);
                 continue;
             }
-            let gamedata_with_frames = match GameData::get_game_data(&path, false) {
-                Ok(gd) => gd,
-                Err(e) => {
-                    pb.println(format!("Error {:?} when parsing game {:?}", e, path));
-                    pb.inc(1);
-                    continue;
+            match GameData::from_replay(&path, &np_code) {
+                Ok(game) => {
+                    summary.parsed += 1;
+                    progress.parsed(&path, &game);
+                    results.add_game(game);
+                }
+                Err(GameParseError::IncorrectPlayerCount) => {
+                    summary.skipped += 1;
+                    progress.skipped(&path, SkipReason::NotSingles);
+                }
+                Err(GameParseError::GameDoesNotContainPlayer) => {
+                    summary.skipped += 1;
+                    progress.skipped(&path, SkipReason::MissingPlayer);
                 }
-            };
-            let result = match GameData::parse_game(gamedata_with_frames, np_code.to_string()) {
-                Ok(g) => g,
                 Err(e) => {
-                    pb.println(format!("Error {:?}, when parsing game: {:?}", e, path));
-                    pb.inc(1);
-                    continue;
+                    summary.failed += 1;
+                    progress.failed(&path, &e);
                 }
-            };
-            results.add_game(result);
-            pb.inc(1);
+            }
         }
-        let serial = serde_json::to_string(&results).unwrap();
-        fs::write(cache_path, serial).unwrap();
-        pb.finish_and_clear();
-        let end = start.elapsed();
-        println!("{} replays scanned in {}", total, HumanDuration(end));
-        results
+        cache.save(&results)?;
+        summary.elapsed = start.elapsed();
+        progress.finished(&summary);
+        Ok(results)
     }
 
     pub fn add_game(&mut self, game: GameData) {
         self.results.push(game);
     }
 
-    pub fn winrate<T: GameDataCondition + fmt::Display>(&self, arg: T) {
+    pub fn retain<T: GameDataCondition>(&mut self, arg: &T) {
+        self.results.retain(|game| arg.game_data_condition(game));
+    }
+
+    pub fn winrate<T: GameDataCondition + fmt::Display>(&self, arg: T) -> WinrateReport {
+        WinrateReport {
+            label: format!("{}", arg),
+            record: self.win_loss(&arg),
+        }
+    }
+
+    fn win_loss<T: GameDataCondition>(&self, arg: &T) -> WinLossData {
         let mut win_loss_data = WinLossData::new();
 
         for game in &self.results {
@@ -258,10 +315,14 @@ impl PlayerData {
                 win_loss_data.add_game(game.is_victory());
             }
         }
-        println!("{}:\n{}", arg, win_loss_data);
+        win_loss_data
+    }
+
+    pub fn compare(&self, first: &Filter, second: &Filter) -> CompareReport {
+        CompareReport::new(self.winrate(first.clone()), self.winrate(second.clone()))
     }
 
-    pub fn matchups<T: GameDataCondition + Display>(&self, arg: T) {
+    pub fn matchups<T: GameDataCondition + Display>(&self, arg: T) -> BreakdownReport<Character> {
         let mut matchup_data = WinLossVec::<Character>::new();
 
         for game in &self.results {
@@ -269,10 +330,10 @@ impl PlayerData {
                 matchup_data.add_game(game.is_victory(), game.opponent_char as usize);
             }
         }
-        println!("{}:\n{}", arg, matchup_data);
+        matchup_data.into_report(format!("{}", arg))
     }
 
-    pub fn stages<T: GameDataCondition + Display>(&self, arg: T) {
+    pub fn stages<T: GameDataCondition + Display>(&self, arg: T) -> BreakdownReport<Stage> {
         let mut stage_data = WinLossVec::<Stage>::new();
 
         for game in &self.results {
@@ -280,10 +341,10 @@ impl PlayerData {
                 stage_data.add_game(game.is_victory(), game.stage as usize);
             }
         }
-        println!("{}:\n{}", arg, stage_data);
+        stage_data.into_report(format!("{}", arg))
     }
 
-    pub fn characters<T: GameDataCondition + Display>(&self, arg: T) {
+    pub fn characters<T: GameDataCondition + Display>(&self, arg: T) -> BreakdownReport<Character> {
         let mut char_data = WinLossVec::<Character>::new();
 
         for game in &self.results {
@@ -291,10 +352,10 @@ impl PlayerData {
                 char_data.add_game(game.is_victory(), game.player_char as usize);
             }
         }
-        println!("{}:\n{}", arg, char_data);
+        char_data.into_report(format!("{}", arg))
     }
 
-    pub fn matchup(&self, player: Character, opponent: Character) {
+    pub fn matchup(&self, player: Character, opponent: Character) -> BreakdownReport<Stage> {
         let mut stage_data = WinLossVec::<Stage>::new();
 
         for game in &self.results {
@@ -302,19 +363,193 @@ impl PlayerData {
                 stage_data.add_game(game.is_victory(), game.stage as usize);
             }
         }
-        println!("{} vs. {}:\n{}", player, opponent, stage_data);
+        stage_data.into_report(format!("{} vs. {}", player, opponent))
+    }
+
+    pub fn matchup_chart(&self) -> GridReport<Character, Character> {
+        let mut chart_data = WinLossGrid::<Character, Character>::new();
+
+        for game in &self.results {
+            chart_data.add_game(
+                game.is_victory(),
+                game.player_char as usize,
+                game.opponent_char as usize,
+            );
+        }
+        chart_data.into_report(String::from("Matchup chart"))
+    }
+
+    pub fn stage_chart(&self) -> GridReport<Character, Stage> {
+        let mut chart_data = WinLossGrid::<Character, Stage>::new();
+
+        for game in &self.results {
+            chart_data.add_game(
+                game.is_victory(),
+                game.player_char as usize,
+                game.stage as usize,
+            );
+        }
+        chart_data.into_report(String::from("Stage chart"))
     }
 
-    pub fn last(&self, num_games: usize) {
-        let i = self.results.len() - num_games;
-        let end = self.results.len();
+    pub fn ratings(&self) -> Ratings {
+        Ratings::from_games(&self.results)
+    }
 
-        for j in i..end {
-            println!("{}", self.results[j])
+    pub fn rating_history<T: GameDataCondition + Display>(&self, arg: T) -> RatingHistoryReport {
+        let mut games: Vec<&GameData> = self
+            .results
+            .iter()
+            .filter(|g| arg.game_data_condition(g))
+            .collect();
+        games.sort_by_key(|g| g.timestamp);
+        RatingHistoryReport {
+            label: format!("{}", arg),
+            history: RatingHistory::from_games(games.into_iter()),
         }
     }
 
-    pub fn overview(&self) {
+    pub fn sessions(&self) -> SessionsReport {
+        let mut games: Vec<&GameData> = self.results.iter().collect();
+        games.sort_by_key(|g| g.timestamp);
+        let mut sessions: Vec<Session> = Vec::new();
+        for game in games {
+            let continues = sessions.last().is_some_and(|s| {
+                game.timestamp - s.end < Duration::minutes(PlayerData::SESSION_GAP_MINUTES)
+            });
+            if !continues {
+                sessions.push(Session {
+                    start: game.timestamp,
+                    end: game.timestamp,
+                    record: WinLossData::new(),
+                    opponents: Vec::new(),
+                });
+            }
+            let session = sessions.last_mut().unwrap();
+            session.end = game.timestamp;
+            session.record.add_game(game.is_victory());
+            if !session.opponents.contains(&game.opponent_code) {
+                session.opponents.push(game.opponent_code.clone());
+            }
+        }
+        SessionsReport { sessions }
+    }
+
+    pub fn trend<T: GameDataCondition + Display>(&self, arg: T) -> TrendReport {
+        let mut points: Vec<TrendPoint> = Vec::new();
+        let mut games: Vec<&GameData> = self
+            .results
+            .iter()
+            .filter(|g| arg.game_data_condition(g))
+            .collect();
+        games.sort_by_key(|g| g.timestamp);
+        for game in games {
+            let date = game.timestamp.date_naive();
+            let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+            if points.last().is_none_or(|p| p.start != start) {
+                points.push(TrendPoint {
+                    start,
+                    record: WinLossData::new(),
+                });
+            }
+            points
+                .last_mut()
+                .unwrap()
+                .record
+                .add_game(game.is_victory());
+        }
+        TrendReport {
+            label: format!("{}", arg),
+            points,
+        }
+    }
+
+    pub fn deaths<T: GameDataCondition + Display>(&self, arg: T) -> DeathsReport {
+        let width = PlayerData::PERCENT_BIN_WIDTH;
+        let mut bins: Vec<PercentBin> = (0..PlayerData::PERCENT_BINS as u32)
+            .map(|i| PercentBin {
+                start: i * width,
+                end: Some((i + 1) * width),
+                deaths: 0,
+                kills: 0,
+            })
+            .collect();
+        bins.last_mut().unwrap().end = None;
+        let bin = |percent: f32| ((percent.max(0.0) as u32 / width) as usize).min(bins.len() - 1);
+        let mut deaths = Vec::new();
+        let mut kills = Vec::new();
+        for game in self.results.iter().filter(|g| arg.game_data_condition(g)) {
+            deaths.extend(game.death_percents.iter().map(|&p| (bin(p), p)));
+            kills.extend(game.kill_percents.iter().map(|&p| (bin(p), p)));
+        }
+        for &(i, _) in &deaths {
+            bins[i].deaths += 1;
+        }
+        for &(i, _) in &kills {
+            bins[i].kills += 1;
+        }
+        let average = |percents: &[(usize, f32)]| {
+            if percents.is_empty() {
+                return None;
+            }
+            Some(percents.iter().map(|&(_, p)| p as f64).sum::<f64>() / percents.len() as f64)
+        };
+        DeathsReport {
+            label: format!("{}", arg),
+            average_death: average(&deaths),
+            average_kill: average(&kills),
+            bins,
+        }
+    }
+
+    pub fn recent(&self, num_games: usize) -> GamesReport<'_> {
+        let mut games = self.newest_first();
+        games.truncate(num_games);
+        GamesReport { games }
+    }
+
+    pub fn last(&self, num_games: usize) -> GamesReport<'_> {
+        let i = self.results.len().saturating_sub(num_games);
+        GamesReport {
+            games: self.results[i..].iter().collect(),
+        }
+    }
+
+    //every game from the newest back, which is how `game` and the game log number them
+    fn newest_first(&self) -> Vec<&GameData> {
+        let mut games: Vec<&GameData> = self.results.iter().collect();
+        games.sort_by_key(|g| std::cmp::Reverse(g.timestamp));
+        games
+    }
+
+    //the first game is the newest one
+    pub fn game(&self, id: usize) -> Option<&GameData> {
+        self.newest_first().get(id.checked_sub(1)?).copied()
+    }
+
+    //one page of the games matching `log`, in its order
+    pub fn game_log(&self, log: &GameLog) -> GameLogReport<'_> {
+        let mut games: Vec<LoggedGame> = self
+            .newest_first()
+            .into_iter()
+            .enumerate()
+            .map(|(i, game)| LoggedGame { id: i + 1, game })
+            .filter(|g| log.matches(g.game))
+            .collect();
+        log.sort.sort(&mut games);
+        let total = games.len();
+        let start = (log.page.max(1) - 1).saturating_mul(PAGE_SIZE).min(total);
+        let games: Vec<LoggedGame> = games.into_iter().skip(start).take(PAGE_SIZE).collect();
+        GameLogReport {
+            first: if games.is_empty() { 0 } else { start + 1 },
+            games,
+            total,
+            page: log.page.max(1),
+            pages: total.div_ceil(PAGE_SIZE),
+        }
+    }
+
+    pub fn overview(&self) -> OverviewReport {
         let mut char_data = WinLossVec::<Character>::new();
         let mut opponent_data = WinLossVec::<Character>::new();
         let mut stage_data = WinLossVec::<Stage>::new();
@@ -324,9 +559,12 @@ impl PlayerData {
             stage_data.add_game(game.is_victory(), game.stage as usize);
         }
 
-        char_data.print_fb_data(DataType::Characters, char_data.fav_best());
-        opponent_data.print_fb_data(DataType::Opponents, opponent_data.fav_best());
-        stage_data.print_fb_data(DataType::Stages, stage_data.fav_best());
+        OverviewReport {
+            ranking: self.ranking,
+            characters: char_data.fav_best_report(DataType::Characters, self.ranking),
+            opponents: opponent_data.fav_best_report(DataType::Opponents, self.ranking),
+            stages: stage_data.fav_best_report(DataType::Stages, self.ranking),
+        }
     }
 }
 
@@ -336,23 +574,16 @@ impl Default for PlayerData {
     }
 }
 
-impl fmt::Display for DataType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            DataType::Stages => write!(f, "On"),
-            DataType::Characters => write!(f, "As"),
-            DataType::Opponents => write!(f, "Vs."),
-        }
-    }
-}
-
-fn count_replays(path: &Path) -> u64 {
+fn count_replays(path: &Path) -> io::Result<u64> {
     let mut count = 0;
-    for entry in fs::read_dir(path).unwrap() {
-        let path = entry.unwrap().path();
-        if path.extension().unwrap() == "slp" {
+    for entry in fs::read_dir(path)? {
+        if is_replay(&entry?.path()) {
             count += 1;
         }
     }
-    count
+    Ok(count)
+}
+
+fn is_replay(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "slp")
 }
@@ -0,0 +1,123 @@
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+#[cfg(feature = "progress")]
+use indicatif::{HumanDuration, ProgressBar, ProgressStyle};
+
+use crate::gamedata::{GameData, GameParseError};
+
+//events sent while a replay directory is scanned. every method does nothing by default,
+//so implementations only need the ones they care about
+pub trait Progress {
+    fn started(&mut self, _total: u64) {}
+    //the cache was written by an older version, so everything is parsed again
+    fn cache_outdated(&mut self) {}
+    fn parsed(&mut self, _path: &Path, _game: &GameData) {}
+    fn skipped(&mut self, _path: &Path, _reason: SkipReason) {}
+    fn failed(&mut self, _path: &Path, _error: &GameParseError) {}
+    fn finished(&mut self, _summary: &ScanSummary) {}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SkipReason {
+    //already in the cache from an earlier scan
+    Cached,
+    //doubles, or a replay with only one player
+    NotSingles,
+    //the netplay code being scanned for isn't in the game
+    MissingPlayer,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ScanSummary {
+    pub total: u64,
+    pub parsed: u64,
+    pub cached: u64,
+    pub skipped: u64,
+    pub failed: u64,
+    pub elapsed: Duration,
+}
+
+//ignores every event
+impl Progress for () {}
+
+//prints failed replays to stderr and nothing else
+pub struct ErrorLog;
+
+impl Progress for ErrorLog {
+    fn failed(&mut self, path: &Path, error: &GameParseError) {
+        eprintln!("Error {:?} when parsing game {:?}", error, path);
+    }
+}
+
+//the progress bar the program shows on stderr
+#[cfg(feature = "progress")]
+pub struct Bar(ProgressBar);
+
+#[cfg(feature = "progress")]
+impl Bar {
+    pub fn new() -> Self {
+        Bar(ProgressBar::hidden())
+    }
+}
+
+#[cfg(feature = "progress")]
+impl Default for Bar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "progress")]
+impl Progress for Bar {
+    fn started(&mut self, total: u64) {
+        self.0 = ProgressBar::new(total);
+        self.0.set_style(
+            ProgressStyle::default_bar().template(
+                "[{elapsed_precise}] [{wide_bar:.green/white}] {pos}/{len} ({eta_precise})",
+            ),
+        );
+    }
+
+    fn cache_outdated(&mut self) {
+        eprintln!("Cache detected but out of date. Rebuilding.");
+    }
+
+    fn parsed(&mut self, _path: &Path, _game: &GameData) {
+        self.0.inc(1);
+    }
+
+    fn skipped(&mut self, path: &Path, reason: SkipReason) {
+        if reason != SkipReason::Cached {
+            self.0
+                .println(format!("Skipped game {:?}: {}", path, reason));
+        }
+        self.0.inc(1);
+    }
+
+    fn failed(&mut self, path: &Path, error: &GameParseError) {
+        self.0
+            .println(format!("Error {:?} when parsing game {:?}", error, path));
+        self.0.inc(1);
+    }
+
+    fn finished(&mut self, summary: &ScanSummary) {
+        self.0.finish_and_clear();
+        eprintln!(
+            "{} replays scanned in {}",
+            summary.total,
+            HumanDuration(summary.elapsed)
+        );
+    }
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SkipReason::Cached => write!(f, "already cached"),
+            SkipReason::NotSingles => write!(f, "not a singles game"),
+            SkipReason::MissingPlayer => write!(f, "the player isn't in this game"),
+        }
+    }
+}
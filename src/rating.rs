@@ -0,0 +1,286 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::fmt;
+
+use crate::character::Character;
+use crate::gamedata::GameData;
+use crate::parsable_enum::Numbered;
+
+use std::convert::TryFrom;
+
+//constants from Glickman's glicko-2 paper
+const GLICKO2_SCALE: f64 = 173.7178;
+const DEFAULT_RATING: f64 = 1500.0;
+const DEFAULT_DEVIATION: f64 = 350.0;
+const DEFAULT_VOLATILITY: f64 = 0.06;
+const TAU: f64 = 0.5;
+const CONVERGENCE_TOLERANCE: f64 = 0.000_001;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Rating {
+    pub rating: f64,
+    pub deviation: f64,
+    pub volatility: f64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RatingSnapshot {
+    pub date: NaiveDate,
+    pub games: usize,
+    pub rating: Rating,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct RatingHistory {
+    pub snapshots: Vec<RatingSnapshot>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Ratings {
+    pub overall: RatingHistory,
+    pub characters: Vec<(Character, RatingHistory)>,
+}
+
+//one game from the perspective of whoever is being rated
+struct RatedResult {
+    opponent: Rating,
+    score: f64,
+}
+
+impl Rating {
+    pub fn new() -> Self {
+        Self {
+            rating: DEFAULT_RATING,
+            deviation: DEFAULT_DEVIATION,
+            volatility: DEFAULT_VOLATILITY,
+        }
+    }
+
+    fn mu(&self) -> f64 {
+        (self.rating - DEFAULT_RATING) / GLICKO2_SCALE
+    }
+
+    fn phi(&self) -> f64 {
+        self.deviation / GLICKO2_SCALE
+    }
+
+    //deviation grows over rating periods where no games were played
+    fn decay(&self, periods: i64) -> Self {
+        let phi = self.phi();
+        let phi = (phi * phi + periods as f64 * self.volatility * self.volatility).sqrt();
+        Self {
+            deviation: (phi * GLICKO2_SCALE).min(DEFAULT_DEVIATION),
+            ..*self
+        }
+    }
+
+    //a single glicko-2 rating period update
+    fn update(&self, results: &[RatedResult]) -> Self {
+        if results.is_empty() {
+            return self.decay(1);
+        }
+        let mu = self.mu();
+        let phi = self.phi();
+        let mut v_inv = 0.0;
+        let mut delta_sum = 0.0;
+        for result in results {
+            let g = g(result.opponent.phi());
+            let e = expected(mu, result.opponent.mu(), g);
+            v_inv += g * g * e * (1.0 - e);
+            delta_sum += g * (result.score - e);
+        }
+        let v = 1.0 / v_inv;
+        let delta = v * delta_sum;
+        let volatility = self.new_volatility(delta, v);
+        let phi_star = (phi * phi + volatility * volatility).sqrt();
+        let new_phi = 1.0 / (1.0 / (phi_star * phi_star) + 1.0 / v).sqrt();
+        let new_mu = mu + new_phi * new_phi * delta_sum;
+        Self {
+            rating: new_mu * GLICKO2_SCALE + DEFAULT_RATING,
+            deviation: new_phi * GLICKO2_SCALE,
+            volatility,
+        }
+    }
+
+    //illinois algorithm from step 5 of the glicko-2 paper
+    fn new_volatility(&self, delta: f64, v: f64) -> f64 {
+        let phi = self.phi();
+        let a = (self.volatility * self.volatility).ln();
+        let f = |x: f64| {
+            let ex = x.exp();
+            let d = phi * phi + v + ex;
+            (ex * (delta * delta - phi * phi - v - ex)) / (2.0 * d * d) - (x - a) / (TAU * TAU)
+        };
+        let mut big_a = a;
+        let mut big_b = if delta * delta > phi * phi + v {
+            (delta * delta - phi * phi - v).ln()
+        } else {
+            let mut k = 1.0;
+            while f(a - k * TAU) < 0.0 {
+                k += 1.0;
+            }
+            a - k * TAU
+        };
+        let mut f_a = f(big_a);
+        let mut f_b = f(big_b);
+        while (big_b - big_a).abs() > CONVERGENCE_TOLERANCE {
+            let big_c = big_a + (big_a - big_b) * f_a / (f_b - f_a);
+            let f_c = f(big_c);
+            if f_c * f_b <= 0.0 {
+                big_a = big_b;
+                f_a = f_b;
+            } else {
+                f_a /= 2.0;
+            }
+            big_b = big_c;
+            f_b = f_c;
+        }
+        (big_a / 2.0).exp()
+    }
+}
+
+fn g(phi: f64) -> f64 {
+    1.0 / (1.0 + 3.0 * phi * phi / (PI * PI)).sqrt()
+}
+
+fn expected(mu: f64, opponent_mu: f64, g: f64) -> f64 {
+    1.0 / (1.0 + (-g * (mu - opponent_mu)).exp())
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RatingHistory {
+    //games have to be in chronological order. each day is a rating period, and opponents are rated
+    //only from their games against the player since that's all the data there is on them
+    pub fn from_games<'a, I: Iterator<Item = &'a GameData>>(games: I) -> Self {
+        let mut history = RatingHistory::default();
+        let mut current = Rating::new();
+        let mut opponents: HashMap<&str, Rating> = HashMap::new();
+        let mut last_date: Option<NaiveDate> = None;
+        let mut period: Vec<&GameData> = Vec::new();
+
+        for game in games {
+            if game.score().is_none() {
+                continue;
+            }
+            let date = game.timestamp.date_naive();
+            if last_date.is_some_and(|d| d != date) {
+                history.close_period(&mut current, &mut opponents, &period, last_date.unwrap());
+                period.clear();
+            }
+            last_date = Some(date);
+            period.push(game);
+        }
+        if let Some(date) = last_date {
+            history.close_period(&mut current, &mut opponents, &period, date);
+        }
+        history
+    }
+
+    fn close_period<'a>(
+        &mut self,
+        current: &mut Rating,
+        opponents: &mut HashMap<&'a str, Rating>,
+        period: &[&'a GameData],
+        date: NaiveDate,
+    ) {
+        if let Some(previous) = self.snapshots.last() {
+            let idle = (date - previous.date).num_days() - 1;
+            if idle > 0 {
+                *current = current.decay(idle);
+            }
+        }
+        let mut player_results = Vec::new();
+        let mut opponent_results: HashMap<&str, Vec<RatedResult>> = HashMap::new();
+        for &game in period {
+            let score = game.score().unwrap();
+            let opponent = *opponents.entry(game.opponent_code.as_str()).or_default();
+            player_results.push(RatedResult { opponent, score });
+            opponent_results
+                .entry(game.opponent_code.as_str())
+                .or_default()
+                .push(RatedResult {
+                    opponent: *current,
+                    score: 1.0 - score,
+                });
+        }
+        for (code, results) in opponent_results {
+            let rating = opponents.get_mut(code).unwrap();
+            *rating = rating.update(&results);
+        }
+        *current = current.update(&player_results);
+        let games = self.snapshots.last().map_or(0, |s| s.games) + period.len();
+        self.snapshots.push(RatingSnapshot {
+            date,
+            games,
+            rating: *current,
+        });
+    }
+
+    pub fn current(&self) -> Option<&RatingSnapshot> {
+        self.snapshots.last()
+    }
+}
+
+impl Ratings {
+    pub fn from_games(games: &[GameData]) -> Self {
+        let mut sorted: Vec<&GameData> = games.iter().collect();
+        sorted.sort_by_key(|g| g.timestamp);
+        let overall = RatingHistory::from_games(sorted.iter().copied());
+        let mut characters = Vec::new();
+        for i in 0..Character::NUM_VALUES {
+            let character = Character::try_from(i).unwrap();
+            let history = RatingHistory::from_games(
+                sorted
+                    .iter()
+                    .copied()
+                    .filter(|g| g.player_char == character),
+            );
+            if !history.snapshots.is_empty() {
+                characters.push((character, history));
+            }
+        }
+        Self {
+            overall,
+            characters,
+        }
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        //two deviations either side is roughly a 95% interval
+        write!(f, "{:.0} ± {:.0}", self.rating, self.deviation * 2.0)
+    }
+}
+
+impl fmt::Display for RatingSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} ({} games)", self.date, self.rating, self.games)
+    }
+}
+
+impl fmt::Display for RatingHistory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.snapshots.is_empty() {
+            return write!(f, "No data for given input.");
+        }
+        let mut output = String::new();
+        let mut previous: Option<f64> = None;
+        for snapshot in &self.snapshots {
+            output.push_str(&format!("{}", snapshot));
+            if let Some(prev) = previous {
+                output.push_str(&format!(" [{:+.0}]", snapshot.rating.rating - prev));
+            }
+            output.push('\n');
+            previous = Some(snapshot.rating.rating);
+        }
+        write!(f, "{}", output.trim_end())
+    }
+}
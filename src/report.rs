@@ -0,0 +1,728 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::fmt::Display;
+
+use crate::character::Character;
+use crate::gamedata::{self, GameData};
+use crate::rating::{RatingHistory, Ratings};
+use crate::stage::Stage;
+use crate::stats::{self, Ranking};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WinLossData {
+    pub games: usize,
+    pub wins: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WinrateReport {
+    pub label: String,
+    pub record: WinLossData,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BreakdownRow<T> {
+    pub key: T,
+    pub record: WinLossData,
+}
+
+//winrates split up by character or stage, only containing the entries that have games
+#[derive(Debug, Serialize)]
+pub struct BreakdownReport<T> {
+    pub label: String,
+    pub rows: Vec<BreakdownRow<T>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GridReport<R, C> {
+    pub label: String,
+    pub rows: Vec<R>,
+    pub columns: Vec<C>,
+    //indexed by row then column
+    pub cells: Vec<Vec<WinLossData>>,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+pub enum DataType {
+    Stages,
+    Characters,
+    Opponents,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FavBestReport<T> {
+    pub data_type: DataType,
+    pub favorite: Option<BreakdownRow<T>>,
+    pub best: Option<BreakdownRow<T>>,
+    pub worst: Option<BreakdownRow<T>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverviewReport {
+    pub ranking: Ranking,
+    pub characters: FavBestReport<Character>,
+    pub opponents: FavBestReport<Character>,
+    pub stages: FavBestReport<Stage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CompareReport {
+    pub first: WinrateReport,
+    pub second: WinrateReport,
+    pub difference: Option<Difference>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Difference {
+    pub winrate: f64,
+    pub interval: (f64, f64),
+    pub p_value: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GamesReport<'a> {
+    pub games: Vec<&'a GameData>,
+}
+
+//one page of the game log. `first` is the position of the page's first game among all `total`,
+//or 0 if the page is empty
+#[derive(Debug, Serialize)]
+pub struct GameLogReport<'a> {
+    pub games: Vec<LoggedGame<'a>>,
+    pub first: usize,
+    pub total: usize,
+    pub page: usize,
+    pub pages: usize,
+}
+
+//`id` is the game's number counting back from the newest, which `game` takes to show it
+#[derive(Debug, Serialize)]
+pub struct LoggedGame<'a> {
+    pub id: usize,
+    #[serde(flatten)]
+    pub game: &'a GameData,
+}
+
+//everything `game` shows about one replay, the player first and the opponent second
+#[derive(Debug, Serialize)]
+pub struct GameDetailReport {
+    pub game: GameData,
+    pub players: [PlayerDetail; 2],
+    pub stocks: Vec<StockLoss>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlayerDetail {
+    pub code: String,
+    pub character: Character,
+    pub damage_dealt: f32,
+    pub damage_taken: f32,
+    //hits on the opponent after they went a while without being hit
+    pub openings: usize,
+    pub kills: usize,
+    pub kill_moves: Vec<&'static str>,
+    pub death_percents: Vec<f32>,
+    pub l_cancels: usize,
+    pub missed_l_cancels: usize,
+    //button presses per minute, stick movement doesn't count
+    pub apm: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StockLoss {
+    //counted from the start of the replay, so `gamedata::FIRST_FRAME` is frame 0
+    pub frame: usize,
+    pub lost_by: String,
+    pub stocks_left: u8,
+    pub percent: f32,
+    //none for self destructs
+    pub killed_by: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Session {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub record: WinLossData,
+    pub opponents: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionsReport {
+    pub sessions: Vec<Session>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrendPoint {
+    pub start: NaiveDate,
+    pub record: WinLossData,
+}
+
+//winrate per week, only including weeks that have games
+#[derive(Debug, Serialize)]
+pub struct TrendReport {
+    pub label: String,
+    pub points: Vec<TrendPoint>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PercentBin {
+    pub start: u32,
+    //none for the last bin, which holds everything past its start
+    pub end: Option<u32>,
+    pub deaths: usize,
+    pub kills: usize,
+}
+
+//stocks lost and taken, grouped by the percent they were at
+#[derive(Debug, Serialize)]
+pub struct DeathsReport {
+    pub label: String,
+    pub bins: Vec<PercentBin>,
+    pub average_death: Option<f64>,
+    pub average_kill: Option<f64>,
+}
+
+//the result of an sql query, with every value already turned into text
+#[derive(Debug, Serialize)]
+pub struct QueryReport {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RatingHistoryReport {
+    pub label: String,
+    pub history: RatingHistory,
+}
+
+impl WinLossData {
+    pub fn new() -> WinLossData {
+        Self { games: 0, wins: 0 }
+    }
+
+    pub fn add_game(&mut self, is_win: bool) {
+        self.games += 1;
+        if is_win {
+            self.wins += 1;
+        }
+    }
+
+    pub fn winrate(&self) -> f64 {
+        (self.wins as f64) / (self.games as f64) * 100.0
+    }
+
+    pub fn interval(&self) -> (f64, f64) {
+        let (low, high) = stats::wilson_interval(self.wins, self.games, stats::Z_95);
+        (low * 100.0, high * 100.0)
+    }
+}
+
+//winrate and interval are included so consumers of the serialized data don't have to recompute them
+impl Serialize for WinLossData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("WinLossData", 4)?;
+        state.serialize_field("games", &self.games)?;
+        state.serialize_field("wins", &self.wins)?;
+        if self.games == 0 {
+            state.serialize_field("winrate", &None::<f64>)?;
+            state.serialize_field("interval", &None::<(f64, f64)>)?;
+        } else {
+            state.serialize_field("winrate", &Some(self.winrate()))?;
+            state.serialize_field("interval", &Some(self.interval()))?;
+        }
+        state.end()
+    }
+}
+
+impl DataType {
+    pub fn labels(&self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            DataType::Characters => ("Favorite character", "Best character", "Worst character"),
+            DataType::Opponents => (
+                "Most common opponent",
+                "Easiest opponent",
+                "Hardest opponent",
+            ),
+            DataType::Stages => ("Most played stage", "Best stage", "Worst stage"),
+        }
+    }
+}
+
+impl Display for WinLossData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.games == 0 {
+            return write!(f, "No games played.");
+        }
+        let (low, high) = self.interval();
+        write!(
+            f,
+            "Won {} of {} games. ({:.2}%, 95% CI {:.2}%-{:.2}%).",
+            self.wins,
+            self.games,
+            self.winrate(),
+            low,
+            high
+        )
+    }
+}
+
+impl Display for WinrateReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:\n{}", self.label, self.record)
+    }
+}
+
+impl<T: Display> Display for BreakdownReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}:", self.label)?;
+        if self.rows.is_empty() {
+            return write!(f, "No data for given input.");
+        }
+        let mut output = String::from("");
+        for row in &self.rows {
+            output.push_str(&format!("{}: {}\n", row.key, row.record));
+        }
+        write!(f, "{}", output.trim_end())
+    }
+}
+
+impl<R: Display, C: Display> GridReport<R, C> {
+    pub fn cell_text(data: &WinLossData) -> String {
+        if data.games == 0 {
+            return String::from("-");
+        }
+        format!(
+            "{}-{} {:.0}%",
+            data.wins,
+            data.games - data.wins,
+            data.winrate()
+        )
+    }
+}
+
+impl<R: Display, C: Display> Display for GridReport<R, C> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}:", self.label)?;
+        if self.rows.is_empty() {
+            return write!(f, "No data for given input.");
+        }
+        let row_labels: Vec<String> = self.rows.iter().map(|r| format!("{}", r)).collect();
+        let col_labels: Vec<String> = self.columns.iter().map(|c| format!("{}", c)).collect();
+        let label_width = row_labels.iter().map(|l| l.chars().count()).max().unwrap();
+        let col_widths: Vec<usize> = col_labels
+            .iter()
+            .enumerate()
+            .map(|(c, label)| {
+                self.cells
+                    .iter()
+                    .map(|row| Self::cell_text(&row[c]).len())
+                    .chain(std::iter::once(label.chars().count()))
+                    .max()
+                    .unwrap()
+            })
+            .collect();
+
+        let mut output = format!("{:width$}", "", width = label_width);
+        for (label, width) in col_labels.iter().zip(&col_widths) {
+            output.push_str(&format!(" | {:^width$}", label, width = width));
+        }
+        output.push('\n');
+        for (row, label) in self.cells.iter().zip(&row_labels) {
+            output.push_str(&format!("{:width$}", label, width = label_width));
+            for (data, &width) in row.iter().zip(&col_widths) {
+                //padding has to happen before coloring since the escape codes would otherwise count towards the width
+                let cell = format!("{:^width$}", Self::cell_text(data), width = width);
+                //`{:#}` colours the winrates, only for terminals since files would get the escape codes
+                if data.games == 0 || !f.alternate() {
+                    output.push_str(&format!(" | {}", cell));
+                } else {
+                    output.push_str(&format!(" | {}", colorize(&cell, data.winrate())));
+                }
+            }
+            output.push('\n');
+        }
+        write!(f, "{}", output.trim_end())
+    }
+}
+
+pub fn colorize(text: &str, winrate: f64) -> String {
+    let color = if winrate >= 65.0 {
+        "1;32"
+    } else if winrate >= 55.0 {
+        "32"
+    } else if winrate >= 45.0 {
+        "33"
+    } else if winrate >= 35.0 {
+        "31"
+    } else {
+        "1;31"
+    };
+    format!("\x1b[{}m{}\x1b[0m", color, text)
+}
+
+impl<T: Display> Display for FavBestReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let data_labels = self.data_type.labels();
+        let favorite = match &self.favorite {
+            Some(fav) => fav,
+            None => return write!(f, "{}: No data.", data_labels.0),
+        };
+        write!(
+            f,
+            "{}: {} ({} games)",
+            data_labels.0, favorite.key, favorite.record.games
+        )?;
+        for (label, row) in [(data_labels.1, &self.best), (data_labels.2, &self.worst)].iter() {
+            //both of these are always set when favorite is
+            let row = row.as_ref().unwrap();
+            let (low, high) = row.record.interval();
+            write!(
+                f,
+                "\n{}: {} ({:.2}% winrate, 95% CI {:.2}%-{:.2}%, {} games)",
+                label,
+                row.key,
+                row.record.winrate(),
+                low,
+                high,
+                row.record.games
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for OverviewReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Ranked by {}.\n{}\n{}\n{}",
+            self.ranking, self.characters, self.opponents, self.stages
+        )
+    }
+}
+
+impl CompareReport {
+    pub fn new(first: WinrateReport, second: WinrateReport) -> Self {
+        let (a, b) = (&first.record, &second.record);
+        let difference = if a.games == 0 || b.games == 0 {
+            None
+        } else {
+            let (low, high) =
+                stats::difference_interval(a.wins, a.games, b.wins, b.games, stats::Z_95);
+            Some(Difference {
+                winrate: b.winrate() - a.winrate(),
+                interval: (low * 100.0, high * 100.0),
+                p_value: stats::fisher_exact(a.wins, a.games, b.wins, b.games),
+            })
+        };
+        CompareReport {
+            first,
+            second,
+            difference,
+        }
+    }
+}
+
+impl Display for CompareReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}\n{}\n", self.first, self.second)?;
+        let difference = match &self.difference {
+            Some(d) => d,
+            None => return write!(f, "Not enough data to compare."),
+        };
+        writeln!(
+            f,
+            "Difference: {:+.2}% (95% CI {:+.2}% to {:+.2}%).",
+            difference.winrate, difference.interval.0, difference.interval.1
+        )?;
+        let verdict = if difference.p_value < 0.05 {
+            "a significant"
+        } else {
+            "not a significant"
+        };
+        write!(
+            f,
+            "p = {:.3} (Fisher's exact test), {} difference at the 5% level.",
+            difference.p_value, verdict
+        )
+    }
+}
+
+impl Display for GamesReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.games.is_empty() {
+            return write!(f, "No data for given input.");
+        }
+        let lines: Vec<String> = self.games.iter().map(|g| format!("{}", g)).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Display for GameLogReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.total == 0 {
+            return write!(f, "No data for given input.");
+        }
+        if self.games.is_empty() {
+            let pages = if self.pages == 1 { "page" } else { "pages" };
+            return write!(
+                f,
+                "There's no page {}, the log has {} {}.",
+                self.page, self.pages, pages
+            );
+        }
+        writeln!(
+            f,
+            "Games {}-{} of {} (page {} of {}):",
+            self.first,
+            self.first + self.games.len() - 1,
+            self.total,
+            self.page,
+            self.pages
+        )?;
+        let lines: Vec<String> = self
+            .games
+            .iter()
+            .map(|LoggedGame { id, game: g }| {
+                format!(
+                    "#{} {} against {}: {} ({}) {}",
+                    id,
+                    g.timestamp.format("%Y-%m-%d %H:%M"),
+                    g.opponent_code,
+                    g,
+                    g.length(),
+                    g.path.display()
+                )
+            })
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl PlayerDetail {
+    pub fn openings_per_kill(&self) -> Option<f64> {
+        match self.kills {
+            0 => None,
+            kills => Some(self.openings as f64 / kills as f64),
+        }
+    }
+
+    pub fn l_cancel_rate(&self) -> Option<f64> {
+        match self.l_cancels + self.missed_l_cancels {
+            0 => None,
+            total => Some(self.l_cancels as f64 / total as f64 * 100.0),
+        }
+    }
+
+    //like `12/15 (80.0%)`
+    pub fn l_cancel_text(&self) -> String {
+        match self.l_cancel_rate() {
+            Some(rate) => format!(
+                "{}/{} ({:.1}%)",
+                self.l_cancels,
+                self.l_cancels + self.missed_l_cancels,
+                rate
+            ),
+            None => String::from("none"),
+        }
+    }
+}
+
+impl Display for GameDetailReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.game)?;
+        writeln!(
+            f,
+            "{} against {}, {} long. {}",
+            self.game.timestamp.format("%Y-%m-%d %H:%M"),
+            self.game.opponent_code,
+            self.game.length(),
+            self.game.path.display()
+        )?;
+        for player in &self.players {
+            writeln!(f)?;
+            write!(f, "{}", player)?;
+        }
+        if self.stocks.is_empty() {
+            return Ok(());
+        }
+        write!(f, "\n\nStocks:")?;
+        for stock in &self.stocks {
+            write!(f, "\n{}", stock)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for PlayerDetail {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} ({}):", self.code, self.character)?;
+        writeln!(
+            f,
+            "  {:.1}% damage dealt and {:.1}% taken.",
+            self.damage_dealt, self.damage_taken
+        )?;
+        match self.openings_per_kill() {
+            Some(per_kill) => {
+                writeln!(f, "  {} openings, {:.1} per kill.", self.openings, per_kill)?
+            }
+            None => writeln!(f, "  {} openings and no kills.", self.openings)?,
+        }
+        if !self.kill_moves.is_empty() {
+            writeln!(f, "  Kill moves: {}.", self.kill_moves.join(", "))?;
+        }
+        if !self.death_percents.is_empty() {
+            let percents: Vec<String> = self
+                .death_percents
+                .iter()
+                .map(|p| format!("{:.1}%", p))
+                .collect();
+            writeln!(f, "  Stocks lost at {}.", percents.join(", "))?;
+        }
+        write!(
+            f,
+            "  L-cancels: {}. APM: {:.0}.",
+            self.l_cancel_text(),
+            self.apm
+        )
+    }
+}
+
+impl Display for StockLoss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} lost a stock at {:.1}%",
+            gamedata::clock(self.frame),
+            self.lost_by,
+            self.percent
+        )?;
+        match self.killed_by {
+            Some(attack) => write!(f, " to {}", attack)?,
+            None => write!(f, " on their own")?,
+        }
+        write!(f, ", {} left.", self.stocks_left)
+    }
+}
+
+impl Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} - {}: {} Against {}.",
+            self.start.format("%Y-%m-%d %H:%M"),
+            self.end.format("%H:%M"),
+            self.record,
+            self.opponents.join(", ")
+        )
+    }
+}
+
+impl Display for SessionsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.sessions.is_empty() {
+            return write!(f, "No data for given input.");
+        }
+        let lines: Vec<String> = self.sessions.iter().map(|s| format!("{}", s)).collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Display for TrendReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}:", self.label)?;
+        if self.points.is_empty() {
+            return write!(f, "No data for given input.");
+        }
+        let lines: Vec<String> = self
+            .points
+            .iter()
+            .map(|p| format!("Week of {}: {}", p.start, p.record))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl PercentBin {
+    pub fn label(&self) -> String {
+        match self.end {
+            Some(end) => format!("{}-{}%", self.start, end - 1),
+            None => format!("{}%+", self.start),
+        }
+    }
+}
+
+impl DeathsReport {
+    pub fn total_deaths(&self) -> usize {
+        self.bins.iter().map(|b| b.deaths).sum()
+    }
+
+    pub fn total_kills(&self) -> usize {
+        self.bins.iter().map(|b| b.kills).sum()
+    }
+}
+
+impl Display for DeathsReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}:", self.label)?;
+        if let Some(average) = self.average_death {
+            writeln!(
+                f,
+                "Lost {} stocks at {:.1}% on average.",
+                self.total_deaths(),
+                average
+            )?;
+        }
+        if let Some(average) = self.average_kill {
+            writeln!(
+                f,
+                "Took {} stocks at {:.1}% on average.",
+                self.total_kills(),
+                average
+            )?;
+        }
+        if self.average_death.is_none() && self.average_kill.is_none() {
+            return write!(f, "No data for given input.");
+        }
+        let lines: Vec<String> = self
+            .bins
+            .iter()
+            .map(|b| format!("{}: {} lost, {} taken", b.label(), b.deaths, b.kills))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Display for QueryReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.rows.is_empty() {
+            return write!(f, "No rows returned.");
+        }
+        let mut lines = vec![self.headers.join(" | ")];
+        lines.extend(self.rows.iter().map(|row| row.join(" | ")));
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl Display for RatingHistoryReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:\n{}", self.label, self.history)
+    }
+}
+
+impl Display for Ratings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.overall.current() {
+            Some(current) => write!(f, "Overall: {}", current)?,
+            None => return write!(f, "No data for given input."),
+        }
+        for (character, history) in &self.characters {
+            //characters only end up in the list if they have at least one snapshot
+            write!(f, "\n{}: {}", character, history.current().unwrap())?;
+        }
+        Ok(())
+    }
+}
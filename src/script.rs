@@ -0,0 +1,105 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::command::{self, CommandError, Query};
+use crate::config::Config;
+use crate::output::OutputFormat;
+use crate::playerdata::PlayerData;
+
+//a file of interactive mode commands run one after another without asking for anything, like
+//  format markdown
+//  output reports/fox.md
+//  character fox matchups since:7d
+//  weekly_fox
+//blank lines and lines starting with `#` are skipped
+pub enum Step {
+    Query(Query),
+    //for every command after it
+    Format(OutputFormat),
+    //sends the reports after it to a file, or back to stdout without one
+    Output(Option<PathBuf>),
+}
+
+#[derive(Debug)]
+pub struct ScriptError {
+    pub line: usize,
+    pub error: CommandError,
+}
+
+//checks every line before anything runs, so a typo at the end doesn't waste a replay scan.
+//a command missing an argument is an error here instead of a prompt
+pub fn parse(text: &str, config: &Config) -> Result<Vec<Step>, ScriptError> {
+    let mut steps = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        let step =
+            parse_line(&tokens, config).map_err(|error| ScriptError { line: i + 1, error })?;
+        steps.push(step);
+    }
+    Ok(steps)
+}
+
+fn parse_line(tokens: &[&str], config: &Config) -> Result<Step, CommandError> {
+    match (tokens[0].to_lowercase().as_str(), &tokens[1..]) {
+        ("format", []) => Err(CommandError::MissingArgument("output format")),
+        ("format", [name]) => name
+            .to_lowercase()
+            .parse()
+            .map(Step::Format)
+            .map_err(|_| CommandError::InvalidArgument(name.to_string(), "output format")),
+        ("format", rest) => Err(CommandError::InvalidArgument(
+            rest.join(" "),
+            "output format",
+        )),
+        ("output", []) => Ok(Step::Output(None)),
+        ("output", path) => Ok(Step::Output(Some(PathBuf::from(path.join(" "))))),
+        _ => match config.expand_query(tokens) {
+            Some(saved) => {
+                let saved: Vec<&str> = saved.iter().map(|t| t.as_str()).collect();
+                command::parse(&saved).map(Step::Query)
+            }
+            None => command::parse(tokens).map(Step::Query),
+        },
+    }
+}
+
+pub fn run(steps: &[Step], data: &mut PlayerData, mut format: OutputFormat) -> io::Result<()> {
+    let mut out: Box<dyn Write> = Box::new(io::stdout());
+    for step in steps {
+        match step {
+            Step::Query(query) => command::run_to(query, data, format, &mut out)?,
+            Step::Format(new_format) => format = *new_format,
+            Step::Output(path) => {
+                out.flush()?;
+                out = match path {
+                    Some(path) => Box::new(BufWriter::new(create(path)?)),
+                    None => Box::new(io::stdout()),
+                };
+            }
+        }
+    }
+    out.flush()
+}
+
+//makes the folders for paths like `reports/fox.md`, and says which file couldn't be written
+fn create(path: &Path) -> io::Result<File> {
+    let create = || -> io::Result<File> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        File::create(path)
+    };
+    create().map_err(|e| io::Error::new(e.kind(), format!("{:?}: {}", path, e)))
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
@@ -0,0 +1,230 @@
+use serde::Serialize;
+use serde_json::json;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::filter::{Filter, FilterParseError};
+use crate::player::Player;
+use crate::playerdata::PlayerData;
+use crate::progress::Bar;
+use crate::stats::Ranking;
+
+const DEFAULT_LIMIT: usize = 50;
+
+//every endpoint takes the same filter parameters, named after the filter keywords
+const ENDPOINTS: [&str; 15] = [
+    "GET /winrate",
+    "GET /characters",
+    "GET /stages",
+    "GET /matchups",
+    "GET /overview",
+    "GET /chart/matchups",
+    "GET /chart/stages",
+    "GET /games?limit=50",
+    "GET /sessions",
+    "GET /trend",
+    "GET /deaths",
+    "GET /ratings",
+    "GET /rating/history",
+    "GET /status",
+    "POST /rescan",
+];
+//every filter keyword the cli takes, under its shortest name
+pub const FILTER_PARAMS: [&str; 7] = ["char", "opp", "stage", "code", "since", "until", "result"];
+
+//where the replays came from, so they can be scanned again while the server is running
+pub struct Location {
+    pub code: String,
+    pub dir: PathBuf,
+    pub cache_dir: PathBuf,
+    pub filter: Filter,
+    pub ranking: Ranking,
+}
+
+#[derive(Debug)]
+enum ApiError {
+    NotFound(String),
+    MethodNotAllowed,
+    BadRequest(String),
+    Rescan(io::Error),
+}
+
+pub fn run(data: PlayerData, location: Location, port: u16) -> io::Result<()> {
+    let address = format!("127.0.0.1:{}", port);
+    let server = Server::http(&address).map_err(|e| io::Error::other(e.to_string()))?;
+    eprintln!("Serving stats on http://{}", address);
+    let mut data = data;
+    for request in server.incoming_requests() {
+        let result = handle(&request, &mut data, &location);
+        let (status, body) = match result {
+            Ok(body) => (200, body),
+            Err(e) => (e.status(), json!({ "error": format!("{}", e) }).to_string()),
+        };
+        eprintln!("{} {} -> {}", request.method(), request.url(), status);
+        let response = Response::from_string(body)
+            .with_status_code(status)
+            .with_header(header("Content-Type", "application/json"))
+            //front-ends are usually served from a different port
+            .with_header(header("Access-Control-Allow-Origin", "*"));
+        if let Err(e) = request.respond(response) {
+            eprintln!("Couldn't send response: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn handle(
+    request: &Request,
+    data: &mut PlayerData,
+    location: &Location,
+) -> Result<String, ApiError> {
+    let (path, query) = match request.url().split_once('?') {
+        Some((path, query)) => (path, query),
+        None => (request.url(), ""),
+    };
+    let path = path.trim_end_matches('/');
+    if path == "/rescan" {
+        if *request.method() != Method::Post {
+            return Err(ApiError::MethodNotAllowed);
+        }
+        let mut rescanned = PlayerData::parse_dir_with(
+            location.dir.clone(),
+            location.code.clone(),
+            &location.cache_dir,
+            &mut Bar::new(),
+        )
+        .map_err(ApiError::Rescan)?;
+        rescanned.retain(&location.filter);
+        rescanned.set_ranking(location.ranking);
+        *data = rescanned;
+        return Ok(json!({ "games": data.winrate(Player::Player).record.games }).to_string());
+    }
+    if *request.method() != Method::Get {
+        return Err(ApiError::MethodNotAllowed);
+    }
+
+    let mut filter = Filter::new();
+    let mut limit = DEFAULT_LIMIT;
+    for (key, value) in parse_query(query) {
+        if key == "limit" {
+            limit = value
+                .parse()
+                .map_err(|_| ApiError::BadRequest(format!("`{}` is not a valid limit.", value)))?;
+            continue;
+        }
+        if !FILTER_PARAMS.contains(&&key[..]) {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown parameter `{}`, use {}, or limit.",
+                key,
+                FILTER_PARAMS.join(", ")
+            )));
+        }
+        filter
+            .set_keyword(&key, &value.to_lowercase())
+            .map_err(ApiError::from)?;
+    }
+
+    match path {
+        "" => to_json(&json!({ "endpoints": ENDPOINTS, "filter_params": FILTER_PARAMS })),
+        "/status" => to_json(&json!({
+            "code": location.code,
+            "dir": location.dir,
+            "games": data.winrate(Player::Player).record.games,
+        })),
+        "/winrate" => to_json(&data.winrate(filter)),
+        "/characters" => to_json(&data.characters(filter)),
+        "/stages" => to_json(&data.stages(filter)),
+        "/matchups" => to_json(&data.matchups(filter)),
+        "/deaths" => to_json(&data.deaths(filter)),
+        "/trend" => to_json(&data.trend(filter)),
+        "/overview" => to_json(&filtered(data, &filter).overview()),
+        "/chart/matchups" => to_json(&filtered(data, &filter).matchup_chart()),
+        "/chart/stages" => to_json(&filtered(data, &filter).stage_chart()),
+        "/games" => to_json(&filtered(data, &filter).recent(limit)),
+        "/sessions" => to_json(&filtered(data, &filter).sessions()),
+        "/ratings" => to_json(&filtered(data, &filter).ratings()),
+        "/rating/history" => to_json(&filtered(data, &filter).rating_history(Player::Player)),
+        _ => Err(ApiError::NotFound(path.to_string())),
+    }
+}
+
+fn filtered(data: &PlayerData, filter: &Filter) -> PlayerData {
+    let mut filtered = data.clone();
+    filtered.retain(filter);
+    filtered
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, ApiError> {
+    //reports only hold plain data, so this can't fail
+    Ok(serde_json::to_string(value).unwrap())
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+}
+
+pub fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (decode(key), decode(value)),
+            None => (decode(pair), String::new()),
+        })
+        .collect()
+}
+
+//undoes the percent encoding browsers apply, like `captain%20falcon` or `abc%23123`
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 2;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+impl ApiError {
+    fn status(&self) -> u16 {
+        match self {
+            ApiError::NotFound(_) => 404,
+            ApiError::MethodNotAllowed => 405,
+            ApiError::BadRequest(_) => 400,
+            ApiError::Rescan(_) => 500,
+        }
+    }
+}
+
+impl From<FilterParseError> for ApiError {
+    fn from(e: FilterParseError) -> Self {
+        ApiError::BadRequest(format!("{}", e))
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ApiError::NotFound(path) => write!(f, "No endpoint at `{}`.", path),
+            ApiError::MethodNotAllowed => write!(f, "Method not allowed."),
+            ApiError::BadRequest(message) => write!(f, "{}", message),
+            ApiError::Rescan(e) => write!(f, "Couldn't rescan replays: {}", e),
+        }
+    }
+}
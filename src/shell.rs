@@ -0,0 +1,259 @@
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::config::{CompletionType, Config};
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::cell::RefCell;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+
+use crate::character::Character;
+use crate::parsable_enum::Parsable;
+use crate::stage::Stage;
+
+const PROMPT: &str = "> ";
+const HISTORY_FILE: &str = "history.txt";
+const HISTORY_SIZE: usize = 1000;
+//filter keywords, see `FilterKey::from_keyword`
+const CONDITION_KEYWORDS: [&str; 9] = [
+    "vs", "as", "on", "against", "since", "until", "before", "after", "result",
+];
+//the words after a command name, see `command::parse`
+const QUERY_WORDS: [&str; 16] = [
+    "winrate",
+    "characters",
+    "stages",
+    "matchups",
+    "overview",
+    "deaths",
+    "current",
+    "history",
+    "character",
+    "lower",
+    "bound",
+    "shrunk",
+    "html",
+    "svg",
+    "sqlite",
+    "trend",
+];
+
+//what tab completes at the prompt being read
+#[derive(Clone, Default)]
+pub enum Completion {
+    #[default]
+    Nothing,
+    Commands(Vec<&'static str>),
+    //a whole command like `character fox matchups since:30d`, starting with one of these names
+    //or the name of a saved query
+    Query(Vec<&'static str>, Vec<String>),
+    //character or stage names, see `names`
+    Names(Vec<String>),
+    //filter conditions like `fox vs marth against abc#123`
+    Conditions,
+    Path,
+}
+
+pub enum Line {
+    Text(String),
+    //ctrl-c
+    Cancel,
+    //ctrl-d, or the end of piped input
+    Exit,
+}
+
+#[derive(Default)]
+struct ShellHelper {
+    completion: Completion,
+    //opponent codes from the loaded replays, for completing `against`
+    codes: Vec<String>,
+    files: FilenameCompleter,
+}
+
+struct Shell {
+    //`None` if the terminal couldn't be set up, in which case lines are read from stdin as they are
+    editor: Option<Editor<ShellHelper, rustyline::history::DefaultHistory>>,
+    history: Option<PathBuf>,
+}
+
+thread_local! {
+    static SHELL: RefCell<Shell> = RefCell::new(Shell::new());
+}
+
+pub fn read_line(completion: Completion) -> Line {
+    SHELL.with(|shell| shell.borrow_mut().read_line(completion))
+}
+
+pub fn set_codes(codes: Vec<String>) {
+    SHELL.with(|shell| {
+        if let Some(helper) = shell
+            .borrow_mut()
+            .editor
+            .as_mut()
+            .and_then(|e| e.helper_mut())
+        {
+            helper.codes = codes;
+        }
+    });
+}
+
+//for ctrl-d in a sub menu or prompt, where there's no way back out to the main loop
+pub fn exit() -> ! {
+    println!();
+    process::exit(0)
+}
+
+impl Shell {
+    fn new() -> Self {
+        let config = Config::builder()
+            .max_history_size(HISTORY_SIZE)
+            .map(|b| b.completion_type(CompletionType::List).build())
+            .unwrap_or_default();
+        let mut editor = Editor::with_config(config).ok();
+        let history = dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("slippi_stats").join(HISTORY_FILE));
+        if let Some(editor) = editor.as_mut() {
+            editor.set_helper(Some(ShellHelper::default()));
+            if let Some(history) = &history {
+                //there's no history the first time
+                let _ = editor.load_history(history);
+            }
+        }
+        Self { editor, history }
+    }
+
+    fn read_line(&mut self, completion: Completion) -> Line {
+        let editor = match self.editor.as_mut() {
+            Some(editor) => editor,
+            None => return read_stdin(),
+        };
+        if let Some(helper) = editor.helper_mut() {
+            helper.completion = completion;
+        }
+        match editor.readline(PROMPT) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    let _ = editor.add_history_entry(line.as_str());
+                    if let Some(history) = &self.history {
+                        if let Some(dir) = history.parent() {
+                            let _ = std::fs::create_dir_all(dir);
+                        }
+                        let _ = editor.save_history(history);
+                    }
+                }
+                Line::Text(line)
+            }
+            Err(ReadlineError::Interrupted) => Line::Cancel,
+            Err(ReadlineError::Eof) => Line::Exit,
+            Err(e) => {
+                eprintln!("Couldn't read input: {}", e);
+                Line::Exit
+            }
+        }
+    }
+}
+
+fn read_stdin() -> Line {
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => Line::Exit,
+        Ok(_) => Line::Text(input),
+    }
+}
+
+//names are completed as a whole from the start of the line, since they can be several words long
+fn complete_names(line: &str, names: &[String]) -> Vec<Pair> {
+    let typed = line.to_lowercase();
+    let mut pairs: Vec<Pair> = Vec::new();
+    for name in names {
+        if name.to_lowercase().starts_with(&typed) && !pairs.iter().any(|p| p.replacement == *name)
+        {
+            pairs.push(Pair {
+                display: name.clone(),
+                replacement: name.clone(),
+            });
+        }
+    }
+    pairs
+}
+
+pub fn names<T: Parsable>() -> Vec<String> {
+    let mut names: Vec<String> = T::names()
+        .iter()
+        .map(|(_, value)| format!("{}", value))
+        .collect();
+    names.dedup();
+    names
+}
+
+impl ShellHelper {
+    //the last word of a set of conditions, completed based on the keyword before it
+    fn complete_condition(&self, line: &str, words: &[&str]) -> (usize, Vec<Pair>) {
+        let start = line.rfind(' ').map(|i| i + 1).unwrap_or(0);
+        let word = line[start..].to_lowercase();
+        let previous = line[..start].split_whitespace().last().unwrap_or("");
+        let mut candidates: Vec<String> = match previous {
+            "on" | "stage" => names::<Stage>(),
+            "against" | "code" => self.codes.clone(),
+            "result" => vec![String::from("win"), String::from("loss")],
+            "since" | "until" | "before" | "after" | "from" | "to" => Vec::new(),
+            _ => names::<Character>(),
+        };
+        candidates.extend(CONDITION_KEYWORDS.iter().map(|k| k.to_string()));
+        candidates.extend(words.iter().map(|w| w.to_string()));
+        let pairs = candidates
+            .into_iter()
+            .filter(|c| c.to_lowercase().starts_with(&word))
+            .map(|c| Pair {
+                replacement: c.to_lowercase(),
+                display: c,
+            })
+            .collect();
+        (start, pairs)
+    }
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let line = &line[..pos];
+        Ok(match &self.completion {
+            Completion::Nothing => (pos, Vec::new()),
+            Completion::Commands(commands) => {
+                let names: Vec<String> = commands.iter().map(|c| c.to_string()).collect();
+                (0, complete_names(line, &names))
+            }
+            Completion::Query(commands, queries) => {
+                let mut names: Vec<String> = commands.iter().map(|c| c.to_string()).collect();
+                names.extend(queries.iter().cloned());
+                match complete_names(line, &names) {
+                    pairs if pairs.is_empty() => self.complete_condition(line, &QUERY_WORDS),
+                    pairs => (0, pairs),
+                }
+            }
+            Completion::Names(names) => (0, complete_names(line, names)),
+            Completion::Conditions => self.complete_condition(line, &[]),
+            Completion::Path => return self.files.complete(line, pos, ctx),
+        })
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
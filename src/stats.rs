@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+//z-score for a two-sided 95% interval
+pub const Z_95: f64 = 1.959_963_984_540_054;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub enum Ranking {
+    //ranks by the lower bound of the wilson interval for best, and the upper bound for worst
+    #[default]
+    LowerBound,
+    //ranks by the winrate after adding `prior_games` imaginary games at `prior_winrate` (a beta prior)
+    Shrunk {
+        prior_games: f64,
+        prior_winrate: f64,
+    },
+}
+
+pub fn wilson_interval(wins: usize, games: usize, z: f64) -> (f64, f64) {
+    if games == 0 {
+        return (0.0, 1.0);
+    }
+    let n = games as f64;
+    let p = wins as f64 / n;
+    let z2 = z * z;
+    let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+    let margin = (z / (1.0 + z2 / n)) * ((p * (1.0 - p) / n) + z2 / (4.0 * n * n)).sqrt();
+    ((center - margin).max(0.0), (center + margin).min(1.0))
+}
+
+pub fn shrunk_winrate(wins: usize, games: usize, prior_games: f64, prior_winrate: f64) -> f64 {
+    let alpha = prior_games * prior_winrate;
+    (wins as f64 + alpha) / (games as f64 + prior_games)
+}
+
+impl Ranking {
+    pub fn best_score(&self, wins: usize, games: usize) -> f64 {
+        match *self {
+            Ranking::LowerBound => wilson_interval(wins, games, Z_95).0,
+            Ranking::Shrunk {
+                prior_games,
+                prior_winrate,
+            } => shrunk_winrate(wins, games, prior_games, prior_winrate),
+        }
+    }
+
+    pub fn worst_score(&self, wins: usize, games: usize) -> f64 {
+        match *self {
+            Ranking::LowerBound => wilson_interval(wins, games, Z_95).1,
+            Ranking::Shrunk {
+                prior_games,
+                prior_winrate,
+            } => shrunk_winrate(wins, games, prior_games, prior_winrate),
+        }
+    }
+}
+
+impl fmt::Display for Ranking {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Ranking::LowerBound => write!(f, "95% confidence lower bound"),
+            Ranking::Shrunk {
+                prior_games,
+                prior_winrate,
+            } => write!(
+                f,
+                "shrunk winrate ({} games at {:.2}% prior)",
+                prior_games,
+                prior_winrate * 100.0
+            ),
+        }
+    }
+}
+
+//two-sided p-value for the difference between two winrates. uses fisher's exact test, so it's valid
+//even for the small samples that a pooled z-test would get wrong
+pub fn fisher_exact(wins_a: usize, games_a: usize, wins_b: usize, games_b: usize) -> f64 {
+    let total = games_a + games_b;
+    let total_wins = wins_a + wins_b;
+    let mut ln_fact = vec![0.0; total + 1];
+    for i in 1..=total {
+        ln_fact[i] = ln_fact[i - 1] + (i as f64).ln();
+    }
+    //probability of seeing `wins` of the total wins in group a, given the row and column totals
+    let prob = |wins: usize| {
+        (ln_fact[total_wins] + ln_fact[total - total_wins] + ln_fact[games_a] + ln_fact[games_b]
+            - ln_fact[total]
+            - ln_fact[wins]
+            - ln_fact[games_a - wins]
+            - ln_fact[total_wins - wins]
+            - ln_fact[games_b + wins - total_wins])
+            .exp()
+    };
+    let observed = prob(wins_a);
+    let min_wins = total_wins.saturating_sub(games_b);
+    let max_wins = total_wins.min(games_a);
+    let p: f64 = (min_wins..=max_wins)
+        .map(prob)
+        .filter(|&p| p <= observed * (1.0 + 1e-7))
+        .sum();
+    p.min(1.0)
+}
+
+//wald interval for the difference in winrate (b - a)
+pub fn difference_interval(
+    wins_a: usize,
+    games_a: usize,
+    wins_b: usize,
+    games_b: usize,
+    z: f64,
+) -> (f64, f64) {
+    let p_a = wins_a as f64 / games_a as f64;
+    let p_b = wins_b as f64 / games_b as f64;
+    let se = (p_a * (1.0 - p_a) / games_a as f64 + p_b * (1.0 - p_b) / games_b as f64).sqrt();
+    let diff = p_b - p_a;
+    (diff - z * se, diff + z * se)
+}
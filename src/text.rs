@@ -1,27 +1,167 @@
+pub const USAGE_TEXT: &str = "Usage: slippi_stats [OPTIONS] [COMMAND]
+
+Runs COMMAND and exits, or starts the interactive mode if no command (or `interactive`) is given.
+`tui` opens a full screen dashboard instead, and `serve` answers JSON requests on localhost.
+
+Options:
+  --code <CODE>      Netplay code to get stats for, like ABC#123.
+  --dir <DIR>        Directory where your replays are stored.
+  --since <DATE>     Only count games from this date on (YYYY-MM-DD, YYYY-MM, a month name, or like 30d).
+  --until <DATE>     Only count games from before this date.
+  --format <FMT>     How to print results: text (default), table, json, csv or markdown.
+  --port <PORT>      Port for `serve` to listen on (default 7878).
+  --cache-dir <DIR>  Directory to keep the parsed replay cache in.
+  --config <FILE>    Config file to use instead of the one in your config directory.
+  --profile <NAME>   Profile to use for this run instead of the active one.
+  -h, --help         Prints this message.
+
+Commands:
+  player winrate|characters|stages|matchups|overview|deaths [CONDITIONS]
+  character <CHARACTER> winrate|stages|matchups|deaths [CONDITIONS]
+  stage <STAGE> winrate|characters|matchups|deaths [CONDITIONS]
+  matchup <CHARACTER> <CHARACTER>
+  chart matchups|stages
+  compare <CONDITIONS> / <CONDITIONS>
+  last <NUMBER>
+  games [CONDITIONS] [sort:newest|oldest|longest|shortest] [page:N] [search:TEXT]
+  game <NUMBER> [CONDITIONS]|<REPLAY>
+  rating current|history|character <CHARACTER>
+  sessions
+  trend
+  export html <FILE>
+  export svg trend|matchups|stages|deaths <FILE>
+  export sqlite <FILE>
+  export frames <FILE> [game:N] [CONDITIONS]
+  export dolphin <FILE> [game:N] [clips:deaths|kills] [below:P] [above:P] [CONDITIONS]
+  sql <QUERY>
+  run <FILE>
+  config show|path
+  config set <KEY> <VALUE>
+  config unset <KEY>
+  config alias character|stage <ALIAS> <NAME>
+  config unalias character|stage <ALIAS>
+  config query <NAME> <COMMAND>
+  config unquery <NAME>
+  profile list|add <NAME> <CODE> <DIR>|remove <NAME>|switch <NAME>
+  profile compare <NAME> <NAME> [CONDITIONS]
+  interactive
+  tui
+  serve
+
+matchup, chart, last, rating, sessions and trend also take conditions as words like since:30d or on:fd.
+
+A query saved with config query runs as a command of its own, with any words after its name added on.
+
+Options that aren't given are read from the active profile in the config file,
+which the interactive mode saves your code and replay directory to.
+Config keys: code, dir, cache_dir, format, prior_games, prior_winrate.";
+
+pub const QUERY_HELP_TEXT: &str =
+    "Type `help` followed by another command to get more info on that command.
+Commands can be typed on one line, like `character fox matchups since:30d` or `matchup fox marth`,
+and anything left out is asked for. Ctrl-C or an empty line cancels a command.";
+
 pub const CHARACTER_HELP_TEXT: &str =
-    "Gives commands for getting stats about a specified character.";
-pub const STAGE_HELP_TEXT: &str = "Gives commands for retrieving stats about a specifed stage.";
-pub const MATCHUP_HELP_TEXT: &str = "Commands for finding out stats regarding a given matchup.";
+    "Stats about a specified character: `character <name> <stat> [conditions]`.";
+pub const STAGE_HELP_TEXT: &str =
+    "Stats about a specified stage: `stage <name> <stat> [conditions]`.";
+pub const MATCHUP_HELP_TEXT: &str =
+    "Your winrate on each stage in a given matchup: `matchup <character> <character>`.";
+pub const CHART_HELP_TEXT: &str =
+    "Prints a full chart of winrates for every character you've played against opponents or stages.";
+pub const COMPARE_HELP_TEXT: &str = "Compares the records for two sets of conditions (like before and after a date) and tests whether the difference is significant: `compare <conditions> / <conditions>`.";
 pub const LAST_HELP_TEXT: &str = "Prints the results of the most recent specified number of games.";
-pub const PLAYER_HELP_TEXT: &str = "Commands for getting overall stats about the player.";
+pub const GAMES_HELP_TEXT: &str = "Lists games with their number, date, opponent, characters, stage, result, length and replay file, 20 to a page: `games [conditions] [sort:newest|oldest|longest|shortest] [page:N] [search:TEXT]`, like `games vs marth sort:length`. Search matches codes, characters, stages and file names.";
+pub const GAME_HELP_TEXT: &str = "Breaks down one game: damage dealt and taken, openings, kill moves, L-cancels, APM and when each stock was lost. Takes the game's number from `games`, like `game 1` for the latest game or `game 3 vs marth` for the third latest against Marth, or the path of a replay file.";
+pub const PLAYER_HELP_TEXT: &str = "Overall stats about the player: `player <stat> [conditions]`.";
+pub const RATING_HELP_TEXT: &str =
+    "Commands for a Glicko-2 skill rating estimated from your results and who they were against.";
+pub const RANKING_HELP_TEXT: &str =
+    "Changes how the best and worst characters, stages, and opponents are picked in the overview.";
+pub const FORMAT_HELP_TEXT: &str =
+    "Changes how results are printed: text (the default sentences), table, json, csv or markdown.";
+pub const SESSIONS_HELP_TEXT: &str =
+    "Lists each play session (games less than an hour apart) with its record and opponents.";
+pub const TREND_HELP_TEXT: &str = "Prints the player's winrate for each week they've played.";
+pub const EXPORT_HELP_TEXT: &str =
+    "Saves stats to a file: a full HTML report, a single SVG chart, an SQLite database, every frame as CSV or Parquet, or a Slippi Dolphin playback queue.";
+pub const SQL_HELP_TEXT: &str = "Runs an SQL query against your games and prints the result.";
+pub const RUN_HELP_TEXT: &str = "Runs every command in a file, one per line: `run weekly.txt`. `format <FMT>` changes the format of the ones after it, `output <FILE>` saves their results to a file and `output` on its own goes back to printing them.";
+pub const CONFIG_HELP_TEXT: &str =
+    "Shows or changes the saved settings, like your code, replay directory and output format.";
+pub const PROFILE_HELP_TEXT: &str =
+    "Lists, adds, removes and switches between profiles, each with its own code and replays.";
 pub const CHANGECACHE_HELP_TEXT: &str =
     "Changes the location where replay data is being loaded from.";
-pub const END_HELP_TEXT: &str = "Ends the program.";
+pub const END_HELP_TEXT: &str = "Ends the program. Ctrl-D does the same from anywhere.";
 
 pub const P_WINRATE_HELP_TEXT: &str = "Player's overall winrate.";
 pub const P_CHARACTERS_HELP_TEXT: &str = "Player's winrate with each character they've used.";
 pub const P_STAGES_HELP_TEXT: &str = "Player's winrate on each stage they've played on.";
 pub const P_OVERVIEW_HELP_TEXT: &str = "Gives and overview of the player's stats with best and favorite character, stage, and opponent.";
+pub const P_DEATHS_HELP_TEXT: &str =
+    "Shows the percents the player's stocks were lost and taken at, grouped every 20%.";
 pub const P_MATCHUPS_HELP_TEXT: &str = "Gives player's winrate against each character.";
 
 pub const C_WINRATE_HELP_TEXT: &str = "Get the overall winrate for the given character.";
 pub const C_STAGES_HELP_TEXT: &str = "Gives the per-stage winrate data for the given character.";
+pub const C_DEATHS_HELP_TEXT: &str =
+    "Shows the percents stocks were lost and taken at while playing the given character.";
 pub const C_MATCHUPS_HELP_TEXT: &str = "Prints the winrate data for the specified character versus every character it has played against.";
 
 pub const S_WINRATE_HELP_TEXT: &str = "Shows the player's overall winrate on the specified stage.";
 pub const S_CHARACTERS_HELP_TEXT: &str =
     "Prints the winrate as every character on the stage that there is data for.";
+pub const S_DEATHS_HELP_TEXT: &str =
+    "Shows the percents stocks were lost and taken at on the given stage.";
 pub const S_MATCHUPS_HELP_TEXT: &str =
     "Gives the winrate versus every character that there is data for on the given stage.";
 
+pub const CH_MATCHUPS_HELP_TEXT: &str =
+    "Prints a grid of the winrate for each of your characters against each opponent character.";
+pub const CH_STAGES_HELP_TEXT: &str =
+    "Prints a grid of the winrate for each of your characters on each stage.";
+
+pub const EX_HTML_HELP_TEXT: &str = "Saves a single HTML file with tables, charts and recent games that can be opened in any browser.";
+pub const EX_FRAMES_HELP_TEXT: &str = "Saves every frame of your replays as rows for notebooks and other tools: position, action state, percent, stocks, stick and button inputs and hitlag for each port. Writes Parquet if the file ends in .parquet and CSV otherwise. `export frames <file> [game:N] [conditions]`, like `export frames fox.parquet as:fox since:30d` or `export frames last.csv game:1`.";
+pub const EX_DOLPHIN_HELP_TEXT: &str = "Saves a playback queue that Slippi Dolphin can open with -i, to review games straight from the stats: `export dolphin <file> [game:N] [clips:deaths|kills] [below:P] [above:P] [conditions]`. Without clips every matching game is queued, like `export dolphin review.json result:loss vs:marth on:fd since:october`. With clips each stock lost (deaths) or taken (kills) is queued from 8 seconds before to 2 seconds after, like `export dolphin review.json clips:deaths below:80`.";
+pub const EX_SVG_HELP_TEXT: &str = "Saves one chart as an SVG image: the weekly winrate trend, winrate per matchup, share of games per stage, or percent at stock loss.";
+pub const EX_SQLITE_HELP_TEXT: &str =
+    "Saves every game, stock and move to an SQLite database for running your own queries. An earlier export at the same path is replaced, any other file is left alone.";
+
+pub const SQL_TABLES_TEXT: &str = "Tables: players (id, code), games (id, played_at, stage, player_id, player_character, opponent_id, opponent_character, result, end_type, quit_port), stocks (game_id, player_id, stock, percent), moves (game_id, player_id, attack_id, move, hits, damage).";
+
+pub const CO_SHOW_HELP_TEXT: &str = "Prints where the config file is and what's in it.";
+pub const CO_SET_HELP_TEXT: &str =
+    "Sets code, dir, cache_dir, format, prior_games or prior_winrate for the active profile.";
+pub const CO_UNSET_HELP_TEXT: &str =
+    "Removes a setting from the active profile so the default is used again.";
+
+pub const CO_ALIAS_HELP_TEXT: &str =
+    "Adds your own name for a character or stage, like `cf` for Captain Falcon.";
+pub const CO_UNALIAS_HELP_TEXT: &str = "Removes a character or stage alias.";
+
+pub const CO_QUERY_HELP_TEXT: &str = "Saves a command under a name to run it by, like `weekly_fox` for `character fox matchups since:7d format:table`.";
+pub const CO_UNQUERY_HELP_TEXT: &str = "Removes a saved query.";
+
+pub const PR_LIST_HELP_TEXT: &str =
+    "Lists every profile with its code and replay directory, marking the active one.";
+pub const PR_ADD_HELP_TEXT: &str = "Adds a new profile with its own code and replay directory.";
+pub const PR_REMOVE_HELP_TEXT: &str = "Removes a profile from the config.";
+pub const PR_SWITCH_HELP_TEXT: &str = "Makes another profile the active one and loads its replays.";
+pub const PR_COMPARE_HELP_TEXT: &str =
+    "Compares the winrates of two profiles under the same conditions.";
+
+pub const RA_CURRENT_HELP_TEXT: &str =
+    "Shows your current rating overall and with each character you've played.";
+pub const RA_HISTORY_HELP_TEXT: &str =
+    "Shows how your overall rating has changed after each day you've played.";
+pub const RA_CHARACTER_HELP_TEXT: &str =
+    "Shows how your rating with the given character has changed after each day you've played it.";
+
+pub const R_LOWER_BOUND_HELP_TEXT: &str = "Ranks by the lower end of the 95% confidence interval (upper end for worst), so small samples don't get picked just for a lucky streak. This is the default.";
+pub const R_SHRUNK_HELP_TEXT: &str = "Ranks by winrate after adding a number of imaginary games at a set winrate, pulling small samples towards that winrate.";
+
+pub const FILTER_SYNTAX_TEXT: &str = "Conditions look like `fox vs marth on fd against abc#123 since 2021-01 before march`. Every part is optional, dates can also be relative like `30d` or `2w`, and `result win` or `result loss` keeps only wins or losses.";
+
 pub const _PLACEHOLDER_TEXT: &str = "todo: write text for this command";
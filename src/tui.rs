@@ -0,0 +1,408 @@
+use std::cmp::Ordering;
+use std::io;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::symbols::Marker;
+use ratatui::text::Line;
+use ratatui::widgets::{
+    Axis, Block, Chart, Clear, Dataset, GraphType, Paragraph, Row, Table as TableWidget,
+    TableState, Tabs, Wrap,
+};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::filter::Filter;
+use crate::gamedata::GameData;
+use crate::output::{Report, Table};
+use crate::player::Player;
+use crate::playerdata::PlayerData;
+use crate::report::TrendReport;
+
+const PANES: [&str; 4] = ["Overview", "Matchups", "Games", "Trend"];
+const KEYS_TEXT: &str =
+    "tab/1-4 pane  up/down select  s sort  r reverse  / filter  enter details  q quit";
+
+//a table that can be sorted by any column without touching the underlying rows
+struct SortableTable {
+    table: Table,
+    //indices into `table.rows` in the order they're shown
+    order: Vec<usize>,
+    sort: Option<(usize, bool)>,
+    state: TableState,
+}
+
+struct App {
+    all: PlayerData,
+    data: PlayerData,
+    filter: Filter,
+    pane: usize,
+    overview: String,
+    tables: Vec<SortableTable>,
+    trend: TrendReport,
+    //text being typed into the filter bar, if it's open
+    editing: Option<String>,
+    message: Option<String>,
+    popup: Option<GameData>,
+}
+
+pub fn run(data: PlayerData, filter: Filter) -> io::Result<()> {
+    let mut app = App::new(data, filter);
+    let mut terminal = ratatui::init();
+    let result = app.main_loop(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn new(all: PlayerData, filter: Filter) -> Self {
+        let mut app = Self {
+            data: all.clone(),
+            all,
+            filter,
+            pane: 0,
+            overview: String::new(),
+            tables: Vec::new(),
+            trend: TrendReport {
+                label: String::new(),
+                points: Vec::new(),
+            },
+            editing: None,
+            message: None,
+            popup: None,
+        };
+        app.refresh();
+        app
+    }
+
+    //rebuilds every pane from the full data, keeping the sort each table had
+    fn refresh(&mut self) {
+        self.data = self.all.clone();
+        self.data.retain(&self.filter);
+        self.overview = format!(
+            "{}\n\n{}",
+            self.data.winrate(Player::Player),
+            self.data.overview()
+        );
+        self.trend = self.data.trend(Player::Player);
+        let tables = vec![
+            (
+                "Characters",
+                self.data.characters(Player::Player).to_table(),
+            ),
+            ("Matchups", self.data.matchups(Player::Player).to_table()),
+            ("Games", self.data.recent(usize::MAX).to_table()),
+            ("Weeks", self.trend.to_table()),
+        ];
+        let sorts: Vec<Option<(usize, bool)>> = self.tables.iter().map(|t| t.sort).collect();
+        self.tables = tables
+            .into_iter()
+            .map(|(title, mut table)| {
+                table.title = format!("{} ({} rows)", title, table.rows.len());
+                SortableTable::new(table)
+            })
+            .collect();
+        for (table, sort) in self.tables.iter_mut().zip(sorts) {
+            table.sort = sort;
+            table.apply_sort();
+        }
+    }
+
+    fn main_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && self.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    //returns true when the dashboard should close
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if let Some(input) = &mut self.editing {
+            match key.code {
+                KeyCode::Enter => {
+                    match input.to_lowercase().parse::<Filter>() {
+                        Ok(filter) => {
+                            self.filter = filter;
+                            self.message = None;
+                            self.refresh();
+                        }
+                        Err(e) => self.message = Some(format!("{}", e)),
+                    }
+                    self.editing = None;
+                }
+                KeyCode::Esc => self.editing = None,
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Char(c) => input.push(c),
+                _ => {}
+            }
+            return false;
+        }
+        if self.popup.is_some() {
+            if let KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') = key.code {
+                self.popup = None;
+            }
+            return false;
+        }
+        let table = &mut self.tables[self.pane];
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return true,
+            KeyCode::Tab | KeyCode::Right => self.pane = (self.pane + 1) % PANES.len(),
+            KeyCode::BackTab | KeyCode::Left => {
+                self.pane = (self.pane + PANES.len() - 1) % PANES.len()
+            }
+            KeyCode::Char(c @ '1'..='4') => self.pane = c as usize - '1' as usize,
+            KeyCode::Down | KeyCode::Char('j') => table.state.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => table.state.select_previous(),
+            KeyCode::PageDown => table.state.scroll_down_by(10),
+            KeyCode::PageUp => table.state.scroll_up_by(10),
+            KeyCode::Home => table.state.select_first(),
+            KeyCode::End => table.state.select_last(),
+            KeyCode::Char('s') => table.next_sort(),
+            KeyCode::Char('r') => table.reverse_sort(),
+            KeyCode::Char('/') | KeyCode::Char('f') => {
+                let current = if self.filter.is_empty() {
+                    String::new()
+                } else {
+                    format!("{}", self.filter)
+                };
+                self.editing = Some(current);
+            }
+            KeyCode::Enter if PANES[self.pane] == "Games" => self.open_game(),
+            _ => {}
+        }
+        false
+    }
+
+    fn open_game(&mut self) {
+        let row = match self.tables[self.pane].selected_row() {
+            Some(row) => row,
+            None => return,
+        };
+        //rows in the games table are in the same order as `recent` returns them
+        self.popup = self
+            .data
+            .recent(usize::MAX)
+            .games
+            .get(row)
+            .map(|g| (*g).clone());
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs_area, body, status] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let tabs = Tabs::new(PANES.to_vec())
+            .select(self.pane)
+            .block(Block::bordered().title(" Slippi stats "))
+            .highlight_style(Style::new().bold().green());
+        frame.render_widget(tabs, tabs_area);
+
+        match PANES[self.pane] {
+            "Overview" => {
+                let [text, table] =
+                    Layout::vertical([Constraint::Length(12), Constraint::Min(0)]).areas(body);
+                let overview = Paragraph::new(self.overview.as_str())
+                    .block(Block::bordered().title(" Overview "))
+                    .wrap(Wrap { trim: false });
+                frame.render_widget(overview, text);
+                self.tables[self.pane].render(frame, table);
+            }
+            "Trend" => {
+                let [chart, table] =
+                    Layout::vertical([Constraint::Percentage(50), Constraint::Min(0)]).areas(body);
+                self.draw_trend(frame, chart);
+                self.tables[self.pane].render(frame, table);
+            }
+            _ => self.tables[self.pane].render(frame, body),
+        }
+
+        let status_line = match (&self.editing, &self.message) {
+            (Some(input), _) => Line::from(format!("filter: {}_", input)).yellow(),
+            (None, Some(message)) => Line::from(message.as_str()).red(),
+            (None, None) if self.filter.is_empty() => Line::from(KEYS_TEXT).dark_gray(),
+            (None, None) => {
+                Line::from(format!("filter: {}  |  {}", self.filter, KEYS_TEXT)).dark_gray()
+            }
+        };
+        frame.render_widget(status_line, status);
+
+        if let Some(game) = &self.popup {
+            let area = centered(frame.area(), 60, 12);
+            frame.render_widget(Clear, area);
+            let details = Paragraph::new(game_details(game))
+                .block(Block::bordered().title(" Game "))
+                .wrap(Wrap { trim: false });
+            frame.render_widget(details, area);
+        }
+    }
+
+    fn draw_trend(&self, frame: &mut Frame, area: Rect) {
+        let points: Vec<(f64, f64)> = self
+            .trend
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i as f64, p.record.winrate()))
+            .collect();
+        let first = self.trend.points.first().map(|p| p.start.to_string());
+        let last = self.trend.points.last().map(|p| p.start.to_string());
+        let dataset = Dataset::default()
+            .name("Winrate")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::new().green())
+            .data(&points);
+        let chart = Chart::new(vec![dataset])
+            .block(Block::bordered().title(" Weekly winrate "))
+            .x_axis(
+                Axis::default()
+                    .bounds([0.0, (points.len().max(2) - 1) as f64])
+                    .labels(vec![first.unwrap_or_default(), last.unwrap_or_default()]),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([0.0, 100.0])
+                    .labels(vec!["0%", "50%", "100%"]),
+            );
+        frame.render_widget(chart, area);
+    }
+}
+
+impl SortableTable {
+    fn new(table: Table) -> Self {
+        let mut state = TableState::default();
+        if !table.rows.is_empty() {
+            state.select(Some(0));
+        }
+        Self {
+            order: (0..table.rows.len()).collect(),
+            table,
+            sort: None,
+            state,
+        }
+    }
+
+    fn selected_row(&self) -> Option<usize> {
+        self.state
+            .selected()
+            .and_then(|i| self.order.get(i).copied())
+    }
+
+    //cycles through the columns, starting with the first
+    fn next_sort(&mut self) {
+        self.sort = match self.sort {
+            None => Some((0, true)),
+            Some((column, _)) if column + 1 < self.table.headers.len() => Some((column + 1, true)),
+            Some(_) => None,
+        };
+        self.apply_sort();
+    }
+
+    fn reverse_sort(&mut self) {
+        if let Some((column, ascending)) = self.sort {
+            self.sort = Some((column, !ascending));
+            self.apply_sort();
+        }
+    }
+
+    fn apply_sort(&mut self) {
+        self.order = (0..self.table.rows.len()).collect();
+        if let Some((column, ascending)) = self.sort {
+            let rows = &self.table.rows;
+            self.order.sort_by(|&a, &b| {
+                let ordering = compare_cells(&rows[a][column], &rows[b][column]);
+                if ascending {
+                    ordering
+                } else {
+                    ordering.reverse()
+                }
+            });
+        }
+    }
+
+    fn render(&mut self, frame: &mut Frame, area: Rect) {
+        let headers: Vec<String> = self
+            .table
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| match self.sort {
+                Some((column, true)) if column == i => format!("{} ^", header),
+                Some((column, false)) if column == i => format!("{} v", header),
+                _ => header.clone(),
+            })
+            .collect();
+        let widths: Vec<Constraint> = (0..headers.len())
+            .map(|i| {
+                let width = self
+                    .table
+                    .rows
+                    .iter()
+                    .map(|row| row[i].chars().count())
+                    .chain(std::iter::once(headers[i].chars().count()))
+                    .max()
+                    .unwrap();
+                Constraint::Length(width as u16)
+            })
+            .collect();
+        let rows = self
+            .order
+            .iter()
+            .map(|&i| Row::new(self.table.rows[i].clone()));
+        let widget = TableWidget::new(rows, widths)
+            .header(Row::new(headers).bold())
+            .block(Block::bordered().title(format!(" {} ", self.table.title)))
+            .row_highlight_style(Style::new().reversed());
+        frame.render_stateful_widget(widget, area, &mut self.state);
+    }
+}
+
+//numbers (including percents and dates) sort by value, everything else alphabetically
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    let number = |cell: &str| cell.trim_end_matches('%').parse::<f64>().ok();
+    match (number(a), number(b)) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => a.cmp(b),
+    }
+}
+
+fn game_details(game: &GameData) -> String {
+    let percents = |percents: &[f32]| {
+        let percents: Vec<String> = percents.iter().map(|p| format!("{:.0}%", p)).collect();
+        if percents.is_empty() {
+            String::from("none")
+        } else {
+            percents.join(", ")
+        }
+    };
+    format!(
+        "{}\n\nDate: {}\nOpponent: {}\nStocks lost at: {}\nStocks taken at: {}\n\nesc to close",
+        game,
+        game.timestamp.format("%Y-%m-%d %H:%M"),
+        game.opponent_code,
+        percents(&game.death_percents),
+        percents(&game.kill_percents)
+    )
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}